@@ -0,0 +1,55 @@
+use crate::cache::*;
+use crate::policy::*;
+use rand::seq::SliceRandom;
+use rand::{Rng, RngCore};
+
+/// Adaptive adversary: over a universe of k + 1 pages there is always one
+/// missing from the cache, and requesting it makes every request a fault.
+/// Against a deterministic policy this forces a ratio of k to OPT.
+pub fn adaptive_sequence<P: ReplacementPolicy, R: RngCore>(cache: &mut Cache<P>, k: usize, len: usize, rand: &mut R) -> Vec<usize> {
+    let mut seq = Vec::with_capacity(len);
+    for _ in 0..len {
+        let page = (1..=k + 1).find(|&page| !cache.contains(page)).unwrap();
+        cache.get_page(page, rand);
+        seq.push(page);
+    }
+    seq
+}
+
+/// Oblivious adversary from the H_k lower bound for randomized paging:
+/// uniform requests over k + 1 pages. Any algorithm faults with
+/// probability at least 1 / (k + 1) per request, while OPT faults once
+/// per phase of expected length (k + 1) H_k.
+pub fn oblivious_sequence<R: Rng>(k: usize, len: usize, rand: &mut R) -> Vec<usize> {
+    (0..len).map(|_| rand.gen_range(1..=k + 1)).collect()
+}
+
+/// k-th harmonic number, the competitive ratio of the best randomized
+/// algorithm against an oblivious adversary.
+pub fn harmonic_number(k: usize) -> f64 {
+    (1..=k).map(|i| 1.0 / i as f64).sum()
+}
+
+/// Oblivious sequence behind the 2H_k - 1 lower bound for marking
+/// algorithms: every k-phase first requests `new` pages left out of the
+/// previous phase, then the other k - `new` pages of the previous phase
+/// in random order. Pages come from a universe of 2k.
+pub fn phase_sequence<R: Rng>(k: usize, new: usize, len: usize, rand: &mut R) -> Vec<usize> {
+    assert!(0 < new && new <= k, "a phase brings 1..=k new pages");
+    let mut seq = Vec::with_capacity(len + k);
+    let mut phase = (1..=k).collect::<Vec<usize>>();
+    let mut rest = (k + 1..=2 * k).collect::<Vec<usize>>();
+    while seq.len() < len {
+        rest.shuffle(rand);
+        phase.shuffle(rand);
+        // the first `new` of `rest` swap in for the last `new` of `phase`
+        let (old, dropped) = phase.split_at(k - new);
+        let mut next = rest[..new].to_vec();
+        next.extend_from_slice(old);
+        rest = rest[new..].iter().chain(dropped).copied().collect();
+        seq.extend_from_slice(&next);
+        phase = next;
+    }
+    seq.truncate(len);
+    seq
+}
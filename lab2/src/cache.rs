@@ -1,17 +1,37 @@
-use rand::{Rng};
-use std::{collections::HashSet, vec};
+use crate::layers::*;
+use crate::marking::*;
+use crate::policy::*;
+use crate::prefetch::*;
+use crate::stats::*;
+use rand::RngCore;
+use serde::Deserialize;
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
 pub enum CacheType {
     Fifo, 
     Fwf, 
     Lru, 
     Lfu, 
     Rand, 
-    Rma
+    Rma,
+    #[serde(rename = "LRU-CF")]
+    LruCleanFirst,
+    Equitable
 }
 
 impl CacheType {
+    pub const ALL: [CacheType; 8] = [
+        CacheType::Fifo,
+        CacheType::Fwf,
+        CacheType::Lru,
+        CacheType::Lfu,
+        CacheType::Rand,
+        CacheType::Rma,
+        CacheType::LruCleanFirst,
+        CacheType::Equitable
+    ];
+
     pub fn name(&self) -> &str {
         match self {
             CacheType::Fifo => "FIFO",
@@ -19,154 +39,385 @@ impl CacheType {
             CacheType::Lru  => "LRU",
             CacheType::Lfu  => "LFU",
             CacheType::Rand => "RAND",
-            CacheType::Rma  => "RMA"
+            CacheType::Rma  => "RMA",
+            CacheType::LruCleanFirst => "LRU-CF",
+            CacheType::Equitable => "EQUITABLE"
+        }
+    }
+
+    /// Whether the policy flips coins, so that only an oblivious
+    /// adversary makes sense against it.
+    pub fn randomized(&self) -> bool {
+        matches!(self, CacheType::Rand | CacheType::Rma | CacheType::Equitable)
+    }
+
+    pub fn policy(&self, n: usize, k: usize) -> Box<dyn ReplacementPolicy> {
+        match self {
+            CacheType::Fifo => Box::<Fifo>::default(),
+            CacheType::Fwf  => Box::new(Fwf::new(n, FlushUnmarked)),
+            CacheType::Lru  => Box::new(Lru::new(n)),
+            CacheType::Lfu  => Box::new(Lfu::new(n)),
+            CacheType::Rand => Box::new(Random),
+            CacheType::Rma  => Box::new(Rma::new(n, RandomUnmarked)),
+            CacheType::LruCleanFirst => Box::new(LruCleanFirst::new(n)),
+            CacheType::Equitable => Box::new(Equitable::new(n, k)),
         }
     }
 }
 
-pub struct Cache {
+pub struct Cache<P: ReplacementPolicy = Box<dyn ReplacementPolicy>> {
+    n            : usize,
     k            : usize,
-    cache_type   : CacheType,
-    set_cache    : HashSet<usize>,
-    fifo_pointer : usize,
-    lru_counter  : usize,
-    add_arr      : Vec<usize>,
+    policy       : P,
+    set_cache    : PageSet,
+    victims      : Vec<usize>,
+    time         : usize,
+    stats        : Option<StatsTracker>,
+    events       : Option<Vec<CacheEvent>>,
+    prefetch     : Option<PrefetchState>,
+    candidates   : Vec<usize>,
+    dirty        : PageSet,
+    faults       : usize,
+    write_backs  : usize,
+    write_back_cost : f64,
+    clock        : u64,
+    ttl          : Option<u64>,
+    expiry       : Vec<u64>, // indexed by page
+    expiring     : bool,     // some resident page may expire
+    expirations  : usize,
 }
 
 impl Cache {
     pub fn new(n: usize, k : usize, cache_type : CacheType) -> Self {
-        let add_arr = match cache_type {
-            CacheType::Fifo => vec![0; k], // to keep track of the order of pages
-            CacheType::Lru | CacheType::Lfu | CacheType::Rma => vec![0; n + 1], // to keep additional info
-            _ => vec![0; 0]
-        };
+        Self::with_policy(n, k, cache_type.policy(n, k))
+    }
+}
 
+impl<P: ReplacementPolicy> Cache<P> {
+    pub fn with_policy(n: usize, k: usize, policy: P) -> Self {
         Cache {
+            n,
             k,
-            cache_type,
-            set_cache : HashSet::new(),
-            fifo_pointer : 0,
-            add_arr,
-            lru_counter : 0,
+            policy,
+            set_cache : PageSet::default(),
+            victims : Vec::new(),
+            time : 0,
+            stats : None,
+            events : None,
+            prefetch : None,
+            candidates : Vec::new(),
+            dirty : PageSet::default(),
+            faults : 0,
+            write_backs : 0,
+            write_back_cost : 1.0,
+            clock : 0,
+            ttl : None,
+            expiry : vec![u64::MAX; n + 1],
+            expiring : false,
+            expirations : 0,
         }
     }
 
-    fn fifo_add(&mut self, page: usize) {
-        // removing
-        let to_remove = self.add_arr[self.fifo_pointer];
-        self.set_cache.remove(&to_remove);
-        //adding
-        self.add_arr[self.fifo_pointer] = page;
-        self.set_cache.insert(page);
-        
-        self.fifo_pointer = (self.fifo_pointer + 1) % self.k;
+    pub fn name(&self) -> &str {
+        self.policy.name()
     }
 
-    fn fwf_add(&mut self, page: usize) {
-        // removing
-        if self.set_cache.len() == self.k {
-            self.set_cache.clear();
-        }
-        // adding
-        self.set_cache.insert(page);
+    pub fn policy(&self) -> &P {
+        &self.policy
     }
 
-    fn rand_add(&mut self, page: usize, rand: &mut rand::rngs::ThreadRng) {
-        // removing
-        if self.set_cache.len() == self.k {
-            let rand_idx = (*rand).gen_range(0..self.k);
-            let rand_page = *self.set_cache.iter().nth(rand_idx).unwrap();
-            self.set_cache.remove(&rand_page);
-        }
-        // adding
-        self.set_cache.insert(page);
+    pub fn contains(&self, page: usize) -> bool {
+        self.set_cache.contains(&page)
     }
 
-    fn lru_update(&mut self, page: usize) {
-        self.lru_counter += 1;
-        self.add_arr[page] = self.lru_counter;
+    pub fn len(&self) -> usize {
+        self.set_cache.len()
     }
 
-    fn lru_add(&mut self, page: usize) {     
-        self.lru_update(page);
+    pub fn is_empty(&self) -> bool {
+        self.set_cache.is_empty()
+    }
 
-        // remove
-        if self.set_cache.len() == self.k {
-            let lr_page =
-                *self.set_cache
-                .iter()
-                .min_by_key(|&v| self.add_arr[*v])
-                .unwrap();
-            self.set_cache.remove(&lr_page);
+    pub fn capacity(&self) -> usize {
+        self.k
+    }
+
+    /// Changes the cache size to `k`, evicting pages chosen by the
+    /// policy until the resident ones fit.
+    pub fn resize<R: RngCore>(&mut self, k: usize, rand: &mut R) {
+        assert!(k > 0, "cache size must be positive");
+        self.k = k;
+        self.policy.on_resize(k);
+        let mut victims = std::mem::take(&mut self.victims);
+        while self.set_cache.len() > self.k {
+            self.policy.choose_victims(&self.set_cache, &self.dirty, 0, rand, &mut victims);
+            for victim in victims.drain(..) {
+                self.evict(victim, 0, false);
+            }
         }
-        // add
-        self.set_cache.insert(page);
+        self.victims = victims;
+    }
+
+    /// Starts collecting hit/miss statistics from the next request on.
+    pub fn enable_stats(&mut self) {
+        self.stats = Some(StatsTracker::new(self.n, self.k));
     }
 
-    fn lfu_update(&mut self, page: usize) {
-        self.add_arr[page] += 1;
+    /// Starts logging every insert and eviction from the next request on.
+    pub fn enable_events(&mut self) {
+        self.events = Some(Vec::new());
     }
 
-    fn lfu_add(&mut self, page: usize) {   
-        self.lfu_update(page);
+    pub fn stats(&self) -> Option<&CacheStats> {
+        self.stats.as_ref().map(|tracker| tracker.stats())
+    }
 
-        // remove
-        if self.set_cache.len() == self.k {
-            let lf_page =
-                *self.set_cache
-                .iter()
-                .min_by_key(|&v| self.add_arr[*v])
-                .unwrap();
-            self.set_cache.remove(&lf_page);
-        }
+    pub fn events(&self) -> Option<&[CacheEvent]> {
+        self.events.as_deref()
+    }
+
+    /// Cost charged, on top of the fault, for evicting a dirty page.
+    pub fn set_write_back_cost(&mut self, cost: f64) {
+        self.write_back_cost = cost;
+    }
+
+    pub fn write_backs(&self) -> usize {
+        self.write_backs
+    }
+
+    /// Faults plus write-back cost of every dirty eviction so far.
+    pub fn cost(&self) -> f64 {
+        self.faults as f64 + self.write_backs as f64 * self.write_back_cost
+    }
+
+    /// Time to live of pages fetched without a TTL of their own.
+    pub fn set_ttl(&mut self, ttl: Option<u64>) {
+        self.ttl = ttl;
+    }
+
+    /// Number of resident pages dropped because their TTL ran out.
+    pub fn expirations(&self) -> usize {
+        self.expirations
+    }
 
-        // add
+    /// Prefetched pages are inserted without counting as faults.
+    pub fn set_prefetcher(&mut self, prefetcher: Box<dyn Prefetcher>) {
+        self.prefetch = Some(PrefetchState::new(prefetcher));
+    }
+
+    pub fn prefetch_stats(&self) -> Option<&PrefetchStats> {
+        self.prefetch.as_ref().map(|state| state.stats())
+    }
+
+    fn insert(&mut self, page: usize, prefetched: bool, ttl: Option<u64>) {
         self.set_cache.insert(page);
+        self.expiry[page] = match ttl {
+            Some(ttl) => {
+                self.expiring = true;
+                self.clock.saturating_add(ttl)
+            },
+            None => u64::MAX,
+        };
+        match prefetched {
+            false => self.policy.on_insert(page),
+            true => self.policy.on_prefetch(page),
+        }
+        if let Some(events) = self.events.as_mut() {
+            let event = match prefetched {
+                false => CacheEvent::Insert { time: self.time, page },
+                true => CacheEvent::Prefetch { time: self.time, page },
+            };
+            events.push(event);
+        }
     }
 
-    fn rma_update(&mut self, page: usize) {
-        self.add_arr[page] = 1;
+    /// Drops `victim`, writing it back if dirty. Returns whether it was
+    /// dirty, or None if it was not resident.
+    fn remove(&mut self, victim: usize, prefetched: bool) -> Option<bool> {
+        if !self.set_cache.remove(&victim) {
+            return None;
+        }
+        let dirty = self.dirty.remove(&victim);
+        if dirty {
+            self.write_backs += 1;
+        }
+        if let Some(state) = self.prefetch.as_mut() {
+            state.on_evict(victim, prefetched);
+        }
+        Some(dirty)
+    }
+
+    /// Removes `victim` to make room for `page`.
+    fn evict(&mut self, victim: usize, page: usize, prefetched: bool) {
+        let Some(dirty) = self.remove(victim, prefetched) else { return };
+        if let Some(tracker) = self.stats.as_mut() {
+            tracker.record_eviction(dirty);
+        }
+        if let Some(events) = self.events.as_mut() {
+            events.push(CacheEvent::Evict { time: self.time, victim, page });
+        }
+    }
+
+    /// Drops `page` because its TTL ran out; counted in `expirations`
+    /// rather than in the evictions of the statistics.
+    fn expire(&mut self, page: usize) {
+        let Some(dirty) = self.remove(page, false) else { return };
+        if let Some(tracker) = self.stats.as_mut() {
+            tracker.record_write_back(dirty);
+        }
+        self.expirations += 1;
+        self.policy.on_remove(page);
+        if let Some(events) = self.events.as_mut() {
+            events.push(CacheEvent::Expire { time: self.time, page });
+        }
+    }
+
+    fn is_expired(&self, page: usize) -> bool {
+        self.expiry[page] <= self.clock
     }
 
-    fn rma_add(&mut self, page: usize, rand: &mut rand::rngs::ThreadRng) { 
-        // remove 
-        if self.set_cache.len() == self.k {
-             // unmark if all marked
-            if self.set_cache.iter().all(|&v| self.add_arr[v] == 1) {
-                for page in self.set_cache.iter() {
-                    self.add_arr[*page] = 0;
-                }
+    /// Expired pages go first; the policy is asked only if none has. When
+    /// prefetching, `keep` is the page just requested, which is never
+    /// evicted; returns false if no other page could be.
+    fn make_room<R: RngCore>(&mut self, page: usize, keep: Option<usize>, rand: &mut R) -> bool {
+        let prefetched = keep.is_some();
+        if self.set_cache.len() >= self.k && self.expiring {
+            let mut victims = std::mem::take(&mut self.victims);
+            victims.extend(self.set_cache.iter().copied().filter(|&v| Some(v) != keep && self.is_expired(v)));
+            for victim in victims.drain(..) {
+                self.expire(victim);
+            }
+            self.victims = victims;
+        }
+        if self.set_cache.len() >= self.k {
+            let kept = keep.filter(|keep| self.set_cache.remove(keep));
+            if kept.is_some() && self.set_cache.is_empty() {
+                self.set_cache.extend(kept);
+                return false;
             }
+            let mut victims = std::mem::take(&mut self.victims);
+            self.policy.choose_victims(&self.set_cache, &self.dirty, kept.unwrap_or(page), rand, &mut victims);
+            for victim in victims.drain(..) {
+                self.evict(victim, page, prefetched);
+            }
+            self.victims = victims;
+            self.set_cache.extend(kept);
+        }
+        true
+    }
 
-            let unmarked_count = self.set_cache.iter().filter(|&v| self.add_arr[*v] == 0).count();
-            let rand_idx = (*rand).gen_range(0..unmarked_count);
-            let rand_page = *self.set_cache.iter().filter(|&v| self.add_arr[*v] == 0).nth(rand_idx).unwrap();
-            self.set_cache.remove(&rand_page);
-        }      
-       
-        self.set_cache.insert(page);        
+    fn prefetch<R: RngCore>(&mut self, page: usize, hit: bool, rand: &mut R) {
+        let mut candidates = std::mem::take(&mut self.candidates);
+        if let Some(state) = self.prefetch.as_mut() {
+            state.candidates(page, hit, &mut candidates);
+        }
+        for candidate in candidates.drain(..) {
+            if candidate == 0 || candidate > self.n || self.set_cache.contains(&candidate) {
+                continue;
+            }
+            if !self.make_room(candidate, Some(page), rand) {
+                break;
+            }
+            self.insert(candidate, true, self.ttl);
+            if let Some(state) = self.prefetch.as_mut() {
+                state.on_prefetch(candidate);
+            }
+        }
+        self.candidates = candidates;
     }
 
     /// Returns 1 if page is not in cache, 0 otherwise.
-    pub fn get_page (&mut self, page : usize, rand : &mut rand::rngs::ThreadRng) -> usize {
-        if self.set_cache.contains(&page) {
-            match self.cache_type {
-                CacheType::Lru => self.lru_update(page),
-                CacheType::Lfu => self.lfu_update(page),
-                CacheType::Rma => self.rma_update(page),
-                _ => (),
-            }
+    pub fn get_page<R: RngCore> (&mut self, page : usize, rand : &mut R) -> usize {
+        self.access(page, false, rand)
+    }
+
+    /// Like `get_page`, but a write leaves the page dirty until evicted.
+    pub fn access<R: RngCore>(&mut self, page: usize, write: bool, rand: &mut R) -> usize {
+        self.access_at(page, write, self.time as u64 + 1, None, rand)
+    }
+
+    /// Like `access`, for a request at `time`. A resident page past its
+    /// expiry is dropped and fetched again; a fetched page lives for `ttl`,
+    /// or the cache's default TTL if None. Without timestamps, `access`
+    /// counts time in requests.
+    pub fn access_at<R: RngCore>(&mut self, page: usize, write: bool, time: u64, ttl: Option<u64>, rand: &mut R) -> usize {
+        self.time += 1;
+        self.clock = time;
+        let expired = self.set_cache.contains(&page) && self.is_expired(page);
+        if expired {
+            self.expire(page);
+        }
+        let hit = self.set_cache.contains(&page);
+        if let Some(tracker) = self.stats.as_mut() {
+            tracker.record(page, hit, expired);
+        }
+        if let Some(state) = self.prefetch.as_mut() {
+            state.on_demand(page, hit);
+        }
+
+        let fault = if hit {
+            self.policy.on_hit(page);
             0
         } else {
-            match self.cache_type {
-                CacheType::Fifo  => self.fifo_add(page),
-                CacheType::Fwf   => self.fwf_add(page),
-                CacheType::Rand  => self.rand_add(page, rand),
-                CacheType::Lru   => self.lru_add(page),
-                CacheType::Lfu   => self.lfu_add(page),
-                CacheType::Rma   => self.rma_add(page, rand),
-            }
+            self.make_room(page, None, rand);
+            self.insert(page, false, ttl.or(self.ttl));
             1
+        };
+        self.faults += fault;
+        if write {
+            self.dirty.insert(page);
+            self.policy.on_write(page);
         }
+
+        if self.prefetch.is_some() {
+            self.prefetch(page, hit, rand);
+        }
+        fault
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::SimRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn expired_pages_are_fetched_again() {
+        let mut rand = SimRng::seed_from_u64(42);
+        let mut cache = Cache::new(4, 2, CacheType::Lru);
+        cache.set_ttl(Some(10));
+        cache.enable_stats();
+        assert_eq!(cache.access_at(1, false, 0, None, &mut rand), 1);
+        assert_eq!(cache.access_at(1, false, 9, None, &mut rand), 0);
+        assert_eq!(cache.access_at(1, false, 10, None, &mut rand), 1);
+        // a TTL of its own outlives the default one
+        assert_eq!(cache.access_at(2, false, 10, Some(100), &mut rand), 1);
+        assert_eq!(cache.access_at(2, false, 50, None, &mut rand), 0);
+
+        let stats = cache.stats().unwrap();
+        assert_eq!(cache.expirations(), 1);
+        assert_eq!(stats.expired_misses, 1);
+        assert_eq!(stats.evictions, 0);
+    }
+
+    #[test]
+    fn expired_pages_make_room_before_evictions() {
+        let mut rand = SimRng::seed_from_u64(42);
+        let mut cache = Cache::new(4, 2, CacheType::Lru);
+        cache.enable_stats();
+        cache.enable_events();
+        cache.access_at(1, true, 0, Some(5), &mut rand);
+        cache.access_at(2, false, 1, None, &mut rand);
+        cache.access_at(1, false, 2, None, &mut rand);
+        // LRU would evict 2, but 1 has expired
+        cache.access_at(3, false, 6, None, &mut rand);
+        assert!(!cache.contains(1) && cache.contains(2) && cache.contains(3));
+
+        let stats = cache.stats().unwrap();
+        assert_eq!(cache.expirations(), 1);
+        assert_eq!(stats.evictions, 0);
+        assert_eq!(stats.write_backs, 1);
+        assert!(matches!(cache.events().unwrap()[2], CacheEvent::Expire { time: 4, page: 1 }));
     }
 }
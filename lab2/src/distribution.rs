@@ -1,16 +1,17 @@
-use rand::{thread_rng, Rng};
+use crate::rng::*;
+use rand::{Rng};
 
 #[derive(Clone)]
-pub struct UniDistribution {
+pub struct UniDistribution<R: Rng = SimRng> {
     n: usize,
-    pub generator: rand::rngs::ThreadRng
+    pub generator: R
 }
 
-impl UniDistribution {
-    pub fn new(n: usize) -> Self {
+impl<R: Rng> UniDistribution<R> {
+    pub fn new(n: usize, generator: R) -> Self {
         UniDistribution {
             n,
-            generator: thread_rng()
+            generator
         }
     }
 
@@ -20,16 +21,16 @@ impl UniDistribution {
 }
 
 #[derive(Clone)]
-pub struct ArrDistribution {
+pub struct ArrDistribution<R: Rng = SimRng> {
     dist_vec: Vec<f64>,
-    pub generator: rand::rngs::ThreadRng
+    pub generator: R
 }
 
-impl ArrDistribution {
-    fn new(dist_vec: Vec<f64>) -> Self {
+impl<R: Rng> ArrDistribution<R> {
+    fn new(dist_vec: Vec<f64>, generator: R) -> Self {
         ArrDistribution {
             dist_vec,
-            generator: thread_rng()
+            generator
         }
     }
 
@@ -38,7 +39,7 @@ impl ArrDistribution {
         self.dist_vec.iter().position(|&x| x > r).unwrap()
     }
 
-    pub fn harmonic(max_int: usize) -> Self {
+    pub fn harmonic(max_int: usize, generator: R) -> Self {
         let mut dist_vec: Vec<f64> = vec![0.0; max_int + 1];
         dist_vec[1] = 1.0;
         for i in 2..=max_int {
@@ -48,10 +49,10 @@ impl ArrDistribution {
         let mut dist_vec: Vec<f64> =
             dist_vec.iter().map(|x| x / h_max).collect();
         dist_vec[max_int] = 1.0;
-        Self::new(dist_vec)
+        Self::new(dist_vec, generator)
     }
 
-    pub fn biharmonic(max_int: usize) -> Self {
+    pub fn biharmonic(max_int: usize, generator: R) -> Self {
         let mut dist_vec: Vec<f64> = vec![0.0; max_int + 1];
         dist_vec[1] = 1.0;
         for i in 2..=max_int {
@@ -61,24 +62,24 @@ impl ArrDistribution {
         let mut dist_vec: Vec<f64> =
             dist_vec.iter().map(|x| x / h_max).collect();
         dist_vec[max_int] = 1.0;
-        Self::new(dist_vec)
+        Self::new(dist_vec, generator)
     }
 }
 
 
 #[derive(Clone)]
-pub struct GeoDistribution {
+pub struct GeoDistribution<R: Rng = SimRng> {
     p : f64,
     n: usize,
-    pub generator: rand::rngs::ThreadRng
+    pub generator: R
 }
 
-impl GeoDistribution {
-    pub fn new(p: f64, n: usize) -> Self {
+impl<R: Rng> GeoDistribution<R> {
+    pub fn new(p: f64, n: usize, generator: R) -> Self {
         GeoDistribution {
             p,
             n,
-            generator: thread_rng()
+            generator
         }
     }
 
@@ -94,30 +95,137 @@ impl GeoDistribution {
     }
 }
 
+/// Working-set model: requests are uniform over a set of `size` pages, and
+/// before each request one member is swapped for an outside page with
+/// probability `drift`.
 #[derive(Clone)]
-pub enum Distribution {
-    Uni(UniDistribution),
-    Har(ArrDistribution),
-    Bih(ArrDistribution),
-    Geo(GeoDistribution)
+pub struct WorkingSetDistribution<R: Rng = SimRng> {
+    members : Vec<usize>,
+    outside : Vec<usize>,
+    drift   : f64,
+    pub generator: R
 }
 
-impl Distribution {
+impl<R: Rng> WorkingSetDistribution<R> {
+    pub fn new(size: usize, drift: f64, n: usize, generator: R) -> Self {
+        assert!(0 < size && size <= n, "working set size must be in 1..=n");
+        WorkingSetDistribution {
+            members : (1..=size).collect(),
+            outside : (size + 1..=n).collect(),
+            drift,
+            generator
+        }
+    }
+
+    pub fn generate(&mut self) -> usize {
+        if !self.outside.is_empty() && self.generator.gen_bool(self.drift) {
+            let i = self.generator.gen_range(0..self.members.len());
+            let j = self.generator.gen_range(0..self.outside.len());
+            std::mem::swap(&mut self.members[i], &mut self.outside[j]);
+        }
+        self.members[self.generator.gen_range(0..self.members.len())]
+    }
+}
+
+/// LRU-stack-depth model: keeps the pages in an LRU stack, draws a stack
+/// distance from `depth` and requests the page at that depth.
+#[derive(Clone)]
+pub struct StackDepthDistribution<R: Rng = SimRng> {
+    stack : Vec<usize>, // most recent first
+    depth : Box<Distribution<R>>,
+}
+
+impl<R: Rng> StackDepthDistribution<R> {
+    /// `depth` must draw from 1..=n.
+    pub fn new(n: usize, depth: Distribution<R>) -> Self {
+        StackDepthDistribution {
+            stack : (1..=n).collect(),
+            depth : Box::new(depth),
+        }
+    }
+
+    pub fn generate(&mut self) -> usize {
+        let d = self.depth.generate();
+        let page = self.stack.remove(d - 1);
+        self.stack.insert(0, page);
+        page
+    }
+}
+
+/// Periodic scans: the first `length` requests of every `period` walk
+/// sequentially through 1..=n, picking up where the previous scan stopped,
+/// and the rest come from `background`. A scan longer than the cache
+/// flushes it under LRU.
+#[derive(Clone)]
+pub struct ScanDistribution<R: Rng = SimRng> {
+    n          : usize,
+    length     : usize,
+    period     : usize,
+    time       : usize,
+    cursor     : usize,
+    background : Box<Distribution<R>>,
+}
+
+impl<R: Rng> ScanDistribution<R> {
+    pub fn new(length: usize, period: usize, n: usize, background: Distribution<R>) -> Self {
+        assert!(period > 0, "scan period must be positive");
+        assert!(length <= period, "scan length must not exceed its period");
+        ScanDistribution {
+            n,
+            length,
+            period,
+            time       : 0,
+            cursor     : 0,
+            background : Box::new(background),
+        }
+    }
+
+    pub fn generate(&mut self) -> usize {
+        let in_scan = self.time % self.period < self.length;
+        self.time += 1;
+        if in_scan {
+            self.cursor = self.cursor % self.n + 1;
+            self.cursor
+        } else {
+            self.background.generate()
+        }
+    }
+}
+
+#[derive(Clone)]
+pub enum Distribution<R: Rng = SimRng> {
+    Uni(UniDistribution<R>),
+    Har(ArrDistribution<R>),
+    Bih(ArrDistribution<R>),
+    Geo(GeoDistribution<R>),
+    Ws(WorkingSetDistribution<R>),
+    Stack(StackDepthDistribution<R>),
+    Scan(ScanDistribution<R>)
+}
+
+impl<R: Rng> Distribution<R> {
     pub fn generate(&mut self) -> usize {
         match self {
             Distribution::Uni(dist) => dist.generate(),
             Distribution::Har(dist) => dist.generate(),
             Distribution::Bih(dist) => dist.generate(),
-            Distribution::Geo(dist) => dist.generate()
+            Distribution::Geo(dist) => dist.generate(),
+            Distribution::Ws(dist) => dist.generate(),
+            Distribution::Stack(dist) => dist.generate(),
+            Distribution::Scan(dist) => dist.generate()
         }
     }
 
+    /// Name with the parameters, so that two entries of a kind differ.
     pub fn name(& self) -> String {
         match self {
             Distribution::Uni(_) => "uniform".to_owned(),
             Distribution::Har(_) => "harmonic".to_owned(),
             Distribution::Bih(_) => "biharmonic".to_owned(),
-            Distribution::Geo(_) => "geometric".to_owned()
+            Distribution::Geo(dist) => format!("geometric_p{}", dist.p),
+            Distribution::Ws(dist) => format!("working-set_size{}_drift{}", dist.members.len(), dist.drift),
+            Distribution::Stack(dist) => format!("stack-depth_{}", dist.depth.name()),
+            Distribution::Scan(dist) => format!("scan_length{}_period{}_{}", dist.length, dist.period, dist.background.name())
         }
     }
 }
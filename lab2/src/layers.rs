@@ -0,0 +1,314 @@
+use crate::policy::*;
+use rand::RngCore;
+
+struct Layer {
+    id    : usize,
+    pages : Vec<usize>,
+}
+
+/// Layer representation of the paging work function (Koutsoupias and
+/// Papadimitriou): k disjoint layers L_1 | ... | L_k, L_1 holding just the
+/// last request. A cache is in the support, i.e. consistent with some
+/// optimal offline schedule, iff it holds at least i pages of L_1..L_i for
+/// every i. Pages in no layer are never needed by OPT.
+///
+/// The cache starts out full of k dummy pages n + 1..=n + k, which is how
+/// its free slots look to the work function.
+pub struct LayerPartition {
+    k        : usize,
+    layers   : Vec<Layer>, // L_1 first
+    layer_of : Vec<usize>, // layer id by page, 0 for none
+    next_id  : usize,
+    counts   : Vec<usize>,
+}
+
+impl LayerPartition {
+    pub fn new(n: usize, k: usize) -> Self {
+        let mut layer_of = vec![0; n + k + 1];
+        let layers = (1..=k)
+            .map(|id| {
+                layer_of[n + id] = id;
+                Layer { id, pages: vec![n + id] }
+            })
+            .collect();
+        LayerPartition {
+            k,
+            layers,
+            layer_of,
+            next_id  : k + 1,
+            counts   : vec![0; k + 2],
+        }
+    }
+
+    fn position(&self, page: usize) -> Option<usize> {
+        let id = self.layer_of[page];
+        if id == 0 {
+            return None;
+        }
+        self.layers.iter().position(|layer| layer.id == id)
+    }
+
+    /// Layer of `page`, 1..=k, or k + 1 if it is in none.
+    pub fn depth(&self, page: usize) -> usize {
+        self.position(page).map_or(self.k + 1, |i| i + 1)
+    }
+
+    /// Moves the pages of layer `from` into layer `into`.
+    fn merge(&mut self, from: Layer, into: usize) {
+        let into = &mut self.layers[into];
+        if from.pages.len() > into.pages.len() {
+            for &page in into.pages.iter() {
+                self.layer_of[page] = from.id;
+            }
+            let pages = std::mem::replace(&mut into.pages, from.pages);
+            into.id = from.id;
+            into.pages.extend(pages);
+        } else {
+            for &page in from.pages.iter() {
+                self.layer_of[page] = into.id;
+            }
+            into.pages.extend(from.pages);
+        }
+    }
+
+    /// Updates the layers for a request to `page`.
+    pub fn request(&mut self, page: usize) {
+        match self.position(page) {
+            Some(0) => return,
+            // L_i minus the page joins L_i+1, or leaves the layers if i = k
+            Some(i) => {
+                let mut rest = self.layers.remove(i);
+                rest.pages.retain(|&p| p != page);
+                if i < self.layers.len() {
+                    self.merge(rest, i);
+                } else {
+                    for &p in rest.pages.iter() {
+                        self.layer_of[p] = 0;
+                    }
+                }
+            },
+            // OPT faults: L_1 and L_2 merge
+            None if self.k > 1 => {
+                let first = self.layers.remove(0);
+                self.merge(first, 0);
+            },
+            None => {
+                for &p in self.layers[0].pages.iter() {
+                    self.layer_of[p] = 0;
+                }
+                self.layers.clear();
+            },
+        }
+        self.layer_of[page] = self.next_id;
+        self.layers.insert(0, Layer { id: self.next_id, pages: vec![page] });
+        self.next_id += 1;
+    }
+
+    /// Changes the cache size to `k`: the layers past L_k leave, and each
+    /// added slot joins as a layer of its own, below the others, holding a
+    /// new dummy page.
+    pub fn resize(&mut self, k: usize) {
+        for layer in self.layers.drain(k.min(self.layers.len())..) {
+            for &p in layer.pages.iter() {
+                self.layer_of[p] = 0;
+            }
+        }
+        while self.layers.len() < k {
+            let dummy = self.layer_of.len();
+            self.layer_of.push(self.next_id);
+            self.layers.push(Layer { id: self.next_id, pages: vec![dummy] });
+            self.next_id += 1;
+        }
+        self.k = k;
+        self.counts.resize(k + 2, 0);
+    }
+
+    /// Pushes to `victims` the resident pages whose eviction for `page`,
+    /// the last request (0 for none), keeps the cache in the support.
+    /// Should the cache have left it, the deepest resident pages are pushed.
+    pub fn admissible(&mut self, resident: &PageSet, page: usize, victims: &mut Vec<usize>) {
+        let mut counts = std::mem::take(&mut self.counts);
+        counts.iter_mut().for_each(|count| *count = 0);
+        let requested = (page != 0).then(|| self.depth(page));
+        for depth in requested.into_iter().chain(resident.iter().map(|&v| self.depth(v))) {
+            counts[depth] += 1;
+        }
+        // deepest layer i with at most i pages of L_1..L_i in the cache
+        let mut tight = 0;
+        let mut held = 0;
+        for (i, count) in counts.iter().enumerate().take(self.k + 1).skip(1) {
+            held += count;
+            if held <= i {
+                tight = i;
+            }
+        }
+        self.counts = counts;
+
+        let start = victims.len();
+        victims.extend(resident.iter().copied().filter(|&v| self.depth(v) > tight));
+        if victims.len() == start {
+            let deepest = resident.iter().map(|&v| self.depth(v)).max();
+            victims.extend(resident.iter().copied().filter(|&v| Some(self.depth(v)) == deepest));
+        }
+    }
+}
+
+/// Equitable (Achlioptas, Chrobak and Noga) in the OnlineMin form of
+/// Brodal, Moruz and Negoescu: every request gives the page a fresh random
+/// priority, and a fault evicts the lowest priority page among those that
+/// keep the cache in the support. Priorities are drawn lazily, the first
+/// time an eviction compares them.
+pub struct Equitable {
+    layers     : LayerPartition,
+    priority   : Vec<Option<u64>>, // indexed by page
+    admissible : Vec<usize>,
+}
+
+impl Equitable {
+    pub fn new(n: usize, k: usize) -> Self {
+        Equitable {
+            layers     : LayerPartition::new(n, k),
+            priority   : vec![None; n + 1],
+            admissible : Vec::new(),
+        }
+    }
+
+    fn request(&mut self, page: usize) {
+        self.layers.request(page);
+        self.priority[page] = None;
+    }
+}
+
+impl ReplacementPolicy for Equitable {
+    fn name(&self) -> &str {
+        "EQUITABLE"
+    }
+
+    fn on_hit(&mut self, page: usize) {
+        self.request(page);
+    }
+
+    fn on_insert(&mut self, page: usize) {
+        self.request(page);
+    }
+
+    // the page stays out of the layers until requested
+    fn on_prefetch(&mut self, _page: usize) {}
+
+    fn on_resize(&mut self, k: usize) {
+        self.layers.resize(k);
+    }
+
+    fn choose_victims(&mut self, resident: &PageSet, _dirty: &PageSet, page: usize, rand: &mut dyn RngCore, victims: &mut Vec<usize>) {
+        if page != 0 {
+            self.request(page);
+        }
+        self.admissible.clear();
+        self.layers.admissible(resident, page, &mut self.admissible);
+        let priority = &mut self.priority;
+        victims.extend(self.admissible.iter().copied().min_by_key(|&v| *priority[v].get_or_insert_with(|| rand.next_u64())));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cache::*;
+    use crate::rng::SimRng;
+    use rand::{Rng, SeedableRng};
+
+    fn pages(layers: &LayerPartition) -> Vec<Vec<usize>> {
+        layers.layers.iter().map(|layer| {
+            let mut pages = layer.pages.clone();
+            pages.sort();
+            pages
+        }).collect()
+    }
+
+    /// Whether `cache` holds at least i pages of L_1..L_i for every i.
+    fn in_support(layers: &LayerPartition, cache: &PageSet) -> bool {
+        let mut held = 0;
+        layers.layers.iter().enumerate().all(|(i, layer)| {
+            held += layer.pages.iter().filter(|&p| cache.contains(p)).count();
+            held > i
+        })
+    }
+
+    #[test]
+    fn requests_update_the_layers() {
+        // pages 1..=6, dummies 7, 8 and 9
+        let mut layers = LayerPartition::new(6, 3);
+        layers.request(1);
+        assert_eq!(pages(&layers), [vec![1], vec![7, 8], vec![9]]);
+        layers.request(2);
+        assert_eq!(pages(&layers), [vec![2], vec![1, 7, 8], vec![9]]);
+        layers.request(7);
+        assert_eq!(pages(&layers), [vec![7], vec![2], vec![1, 8, 9]]);
+        layers.request(9);
+        assert_eq!(pages(&layers), [vec![9], vec![7], vec![2]]);
+        assert_eq!(layers.depth(1), 4);
+        layers.request(9);
+        assert_eq!(pages(&layers), [vec![9], vec![7], vec![2]]);
+        layers.request(1);
+        assert_eq!(pages(&layers), [vec![1], vec![7, 9], vec![2]]);
+    }
+
+    #[test]
+    fn resizing_drops_or_adds_the_deepest_layers() {
+        let mut layers = LayerPartition::new(6, 3);
+        for page in [1, 2, 7, 9] {
+            layers.request(page);
+        }
+        layers.resize(2);
+        assert_eq!(pages(&layers), [vec![9], vec![7]]);
+        assert_eq!(layers.depth(2), 3);
+        // added slots are free ones, i.e. new dummy pages
+        layers.resize(4);
+        assert_eq!(pages(&layers), [vec![9], vec![7], vec![10], vec![11]]);
+        layers.request(3);
+        assert_eq!(pages(&layers), [vec![3], vec![7, 9], vec![10], vec![11]]);
+    }
+
+    #[test]
+    fn admissible_victims_keep_the_cache_in_the_support() {
+        let mut rand = SimRng::seed_from_u64(43);
+        for _ in 0..100 {
+            let n = rand.gen_range(1..=10);
+            let k = rand.gen_range(1..=5);
+            let mut layers = LayerPartition::new(n, k);
+            let mut cache = (n + 1..=n + k).collect::<PageSet>();
+            let mut victims = Vec::new();
+            for _ in 0..200 {
+                let page = rand.gen_range(1..=n);
+                layers.request(page);
+                if cache.contains(&page) {
+                    continue;
+                }
+                victims.clear();
+                layers.admissible(&cache, page, &mut victims);
+                for &v in cache.iter() {
+                    let mut next = cache.clone();
+                    next.remove(&v);
+                    next.insert(page);
+                    assert_eq!(victims.contains(&v), in_support(&layers, &next));
+                }
+                cache.remove(&victims[rand.gen_range(0..victims.len())]);
+                cache.insert(page);
+            }
+        }
+    }
+
+    #[test]
+    fn resizing_keeps_one_layer_per_slot() {
+        let mut rand = SimRng::seed_from_u64(44);
+        let mut cache = Cache::with_policy(20, 6, Equitable::new(20, 6));
+        for k in [6, 2, 9, 1, 4] {
+            cache.resize(k, &mut rand);
+            assert_eq!(cache.policy().layers.layers.len(), k);
+            for _ in 0..500 {
+                cache.get_page(rand.gen_range(1..=20), &mut rand);
+                assert!(cache.len() <= k);
+            }
+        }
+    }
+}
@@ -0,0 +1,18 @@
+pub mod adversary;
+pub mod cache;
+pub mod distribution;
+pub mod layers;
+pub mod marking;
+pub mod opt;
+pub mod paired;
+pub mod parallel;
+pub mod plots;
+pub mod policy;
+pub mod prefetch;
+pub mod results;
+pub mod rng;
+pub mod spec;
+pub mod stack_distance;
+pub mod stats;
+pub mod tenant;
+pub mod trace;
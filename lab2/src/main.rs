@@ -1,58 +1,588 @@
-mod cache;
-mod distribution;
+use lab2::adversary::*;
+use lab2::cache::*;
+use lab2::distribution::*;
+use lab2::opt::*;
+use lab2::paired::*;
+use lab2::parallel::*;
+use lab2::plots::*;
+use lab2::prefetch::*;
+use lab2::results::*;
+use lab2::rng::*;
+use lab2::spec::*;
+use lab2::stack_distance::*;
+use lab2::tenant::*;
+use lab2::trace::*;
 
-use cache::*;
-use distribution::*;
+use rand::distributions::WeightedIndex;
+use rand::Rng;
 
-use std::fs::File;
+use std::fs::{self, File};
 use std::io::prelude::*;
+use std::path::Path;
 
-const N: usize = 10;
-const BIN_SEQ: usize = 100;
+/// Creates an output file whose header records the master seed.
+fn create_output(filename: &str, seed: u64) -> File {
+    let mut file = File::create(filename).unwrap();
+    let header = format!("# seed={}\n", seed);
+    file.write_all(header.as_bytes()).unwrap();
+    file
+}
 
-fn experiment() {
-    let mut rand = rand::thread_rng();
-    let num_of_tests = 1_000_000;
-    let ns = [20, 30, 40, 50, 60, 70, 80, 90, 100];
-    let cache_types = [
-        CacheType::Fifo, 
-        CacheType::Fwf, 
-        CacheType::Lru,
-        CacheType::Lfu,
-        CacheType::Rand,
-        CacheType::Rma
-    ];
+/// `reps` sequences of `len` requests from each distribution, in turn.
+fn request_sequences(distributions: &mut [Distribution], reps: usize, len: usize) -> Vec<Vec<Vec<usize>>> {
+    distributions
+        .iter_mut()
+        .map(|dist| {
+            (0..reps)
+                .map(|_| (0..len).map(|_| dist.generate()).collect::<Vec<usize>>())
+                .collect::<Vec<Vec<usize>>>()
+        })
+        .collect()
+}
 
-    for n in ns {
-        let mut distributions = [
-                Distribution::Uni(UniDistribution::new(n)),
-                Distribution::Har(ArrDistribution::harmonic(n)),
-                Distribution::Bih(ArrDistribution::biharmonic(n)),
-                Distribution::Geo(GeoDistribution::new(0.5, n))
-            ];
-        let ks = ((n / 10)..=(n / 5)).collect::<Vec<usize>>();
-        for distribution in distributions.iter_mut() { 
-            for cache_type in cache_types {
-                let filename = format!("data/n_{}dist_{}cache_{}.txt", n, distribution.name(), cache_type.name());
-                let mut file = File::create(filename).unwrap();
+/// Faults of a cache of `cache_type` and of OPT, both of size k, summed
+/// over a sequence from `generate` per repetition. Repetition r draws its
+/// sequence from stream `ids, r, 0` and the cache's coins from `ids, r, 1`.
+fn faults_against_opt<G>(cache_type: CacheType, n: usize, k: usize, reps: u64, seed: u64, ids: &[u64], generate: G) -> (usize, usize)
+where
+    G: Fn(&mut SimRng) -> Vec<usize>,
+{
+    let mut faults = 0;
+    let mut opt = 0;
+    for r in 0..reps {
+        let seq = generate(&mut stream(seed, &[ids, &[r, 0]].concat()));
+        let mut rand = stream(seed, &[ids, &[r, 1]].concat());
+        let mut cache = Cache::new(n, k, cache_type);
+        faults += seq.iter().map(|&page| cache.get_page(page, &mut rand)).sum::<usize>();
+        opt += opt_faults(&seq, k);
+    }
+    (faults, opt)
+}
+
+fn experiment(spec: &ExperimentSpec, seed: u64) {
+    let num_of_tests = spec.requests;
+    let num_of_reps = spec.repetitions;
+    let policies = &spec.policies;
+    fs::create_dir_all(&spec.output_dir).unwrap();
+
+    let mut rows = Vec::new();
+    let mut paired = String::from("n,k,distribution,policy_a,policy_b,mean_diff,ci_low,ci_high,seed\n");
+    for &n in spec.ns.iter() {
+        let mut distributions = spec.distributions(n, seed);
+        let names = distributions.iter().map(|dist| dist.name()).collect::<Vec<String>>();
+        // every policy and k sees the same requests in each repetition
+        let sequences = request_sequences(&mut distributions, num_of_reps, num_of_tests)
+            .into_iter()
+            .enumerate()
+            .map(|(d, reps)| {
+                let mut writes = stream(seed, &[n as u64, d as u64, u64::MAX]);
+                reps.into_iter()
+                    .map(|seq| seq.into_iter().map(|page| (page, writes.gen_bool(spec.write_fraction))).collect())
+                    .collect::<Vec<Vec<(usize, bool)>>>()
+            })
+            .collect::<Vec<Vec<Vec<(usize, bool)>>>>();
+
+        let ks = spec.ks.ks(n);
+        let mut jobs = Vec::new();
+        for d in 0..sequences.len() {
+            for c in 0..policies.len() {
                 for k in ks.iter() {
-                    let mut cache = Cache::new(n, *k, cache_type); 
+                    jobs.push((d, c, *k));
+                }
+            }
+        }
+
+        let outcomes = par_map(&jobs, |&(d, c, k)| {
+            sequences[d]
+                .iter()
+                .enumerate()
+                .map(|(r, seq)| {
+                    let mut rand = stream(seed, &[n as u64, d as u64, c as u64, k as u64, r as u64]);
+                    let mut cache = Cache::new(n, k, policies[c]);
+                    cache.set_write_back_cost(spec.write_back_cost);
                     let mut sum = 0;
-                    for _ in 0..num_of_tests {
-                        sum += cache.get_page(distribution.generate(), &mut rand);
+                    for &(page, write) in seq.iter() {
+                        sum += cache.access(page, write, &mut rand);
                     }
-                    let avg = sum as f64 / num_of_tests as f64;
+                    (sum, cache.write_backs(), cache.cost())
+                })
+                .collect::<Vec<(usize, usize, f64)>>()
+        });
 
-                    let line = format!("{};{}\n", k, avg);
-                    file.write(line.as_bytes()).unwrap();
+        for (&(d, c, k), reps) in jobs.iter().zip(outcomes.iter()) {
+            for (r, &(faults, write_backs, cost)) in reps.iter().enumerate() {
+                rows.push(ResultRow {
+                    n,
+                    k,
+                    distribution : names[d].clone(),
+                    policy       : policies[c].name().to_owned(),
+                    repetition   : r,
+                    requests     : num_of_tests,
+                    faults,
+                    write_backs,
+                    cost,
+                    seed,
+                });
+            }
+        }
+
+        // outcomes are laid out as [d][c][k]
+        let rates = |d: usize, c: usize, i: usize| {
+            outcomes[(d * policies.len() + c) * ks.len() + i]
+                .iter()
+                .map(|&(faults, _, _)| faults as f64 / num_of_tests as f64)
+                .collect::<Vec<f64>>()
+        };
+        for (d, name) in names.iter().enumerate() {
+            for (i, k) in ks.iter().enumerate() {
+                for a in 0..policies.len() {
+                    for b in (a + 1)..policies.len() {
+                        let diff = PairedDiff::new(&rates(d, a, i), &rates(d, b, i));
+                        paired += &format!("{},{},{},{},{},{},{},{},{}\n", n, k, name, policies[a].name(), policies[b].name(),
+                            diff.mean, diff.low(), diff.high(), seed);
+                    }
+                }
+            }
+        }
+    }
+
+    let output_dir = Path::new(&spec.output_dir);
+    write_results(&output_dir.join("results.csv"), &rows).unwrap();
+    fs::write(output_dir.join("paired.csv"), paired).unwrap();
+}
+
+/// Online policies at size k against OPT at every h <= k on the same
+/// sequences, written as a ratio surface to augmentation.csv.
+fn augmentation_experiment(spec: &ExperimentSpec, seed: u64) {
+    let policies = &spec.policies;
+    fs::create_dir_all(&spec.output_dir).unwrap();
+
+    let mut surface = String::from("n,distribution,policy,k,h,faults,opt_faults,ratio,bound,seed\n");
+    for &n in spec.ns.iter() {
+        let mut distributions = spec.distributions(n, seed);
+        let names = distributions.iter().map(|dist| dist.name()).collect::<Vec<String>>();
+        let sequences = request_sequences(&mut distributions, spec.repetitions, spec.requests);
+        let ks = spec.ks.ks(n);
+        let max_k = ks.iter().copied().max().unwrap_or(0);
+
+        // OPT faults for h = 1..=max_k, summed over repetitions
+        let opt = par_map(&(0..sequences.len()).collect::<Vec<usize>>(), |&d| {
+            (1..=max_k)
+                .map(|h| sequences[d].iter().map(|seq| opt_faults(seq, h)).sum::<usize>())
+                .collect::<Vec<usize>>()
+        });
+
+        let mut jobs = Vec::new();
+        for d in 0..sequences.len() {
+            for c in 0..policies.len() {
+                for k in ks.iter() {
+                    jobs.push((d, c, *k));
                 }
             }
         }
+        let faults = par_map(&jobs, |&(d, c, k)| {
+            sequences[d]
+                .iter()
+                .enumerate()
+                .map(|(r, seq)| {
+                    let mut rand = stream(seed, &[n as u64, d as u64, c as u64, k as u64, r as u64]);
+                    let mut cache = Cache::new(n, k, policies[c]);
+                    seq.iter().map(|&page| cache.get_page(page, &mut rand)).sum::<usize>()
+                })
+                .sum::<usize>()
+        });
+
+        for (&(d, c, k), faults) in jobs.iter().zip(faults) {
+            for h in 1..=k {
+                let opt = opt[d][h - 1];
+                surface += &format!("{},{},{},{},{},{},{},{},{},{}\n", n, names[d], policies[c].name(), k, h, faults, opt,
+                    faults as f64 / opt.max(1) as f64, augmentation_bound(k, h), seed);
+            }
+        }
+    }
+
+    fs::write(Path::new(&spec.output_dir).join("augmentation.csv"), surface).unwrap();
+}
+
+fn mrc_experiment(seed: u64) {
+    let num_of_tests = 1_000_000;
+    let ns = [20, 30, 40, 50, 60, 70, 80, 90, 100];
+    let sampling_rate = 0.1;
+
+    for n in ns {
+        let mut distributions = ExperimentSpec::lab().distributions(n, seed);
+        let ks = ((n / 10)..=(n / 5)).collect::<Vec<usize>>();
+        let sequences = request_sequences(&mut distributions, 1, num_of_tests);
+        for (distribution, reps) in distributions.iter().zip(sequences) {
+            let seq = &reps[0];
+
+            let mut exact = StackDistance::new();
+            exact.process(seq);
+            let exact = exact.curve();
+
+            let mut sampled = StackDistance::with_sampling(sampling_rate);
+            sampled.process(seq);
+            let sampled = sampled.curve();
+
+            let mismatched = validate_lru(&exact, seq, n, &ks);
+            if !mismatched.is_empty() {
+                println!("n = {}, dist = {}: LRU mismatch for k in {:?}", n, distribution.name(), mismatched);
+            }
+
+            let filename = format!("data/n_{}dist_{}mrc.txt", n, distribution.name());
+            let mut file = create_output(&filename, seed);
+            for k in 1..=n {
+                let line = format!("{};{};{}\n", k, exact.miss_ratio(k), sampled.miss_ratio(k));
+                file.write_all(line.as_bytes()).unwrap();
+            }
+        }
+    }
+}
+
+fn adversary_experiment(seed: u64) {
+    let num_of_tests = 100_000;
+    let num_of_reps = 10;
+    let ks = (2..=20).collect::<Vec<usize>>();
+
+    let mut jobs = Vec::new();
+    for c in 0..CacheType::ALL.len() {
+        for k in ks.iter() {
+            jobs.push((c, *k));
+        }
+    }
+
+    let ratios = par_map(&jobs, |&(c, k)| {
+        let cache_type = CacheType::ALL[c];
+        match cache_type {
+            _ if cache_type.randomized() => {
+                let (faults, opt) = faults_against_opt(cache_type, k + 1, k, num_of_reps, seed, &[c as u64, k as u64],
+                    |rand| oblivious_sequence(k, num_of_tests, rand));
+                (faults as f64 / opt as f64, harmonic_number(k))
+            },
+            _ => {
+                let mut rand = stream(seed, &[c as u64, k as u64]);
+                let mut cache = Cache::new(k + 1, k, cache_type);
+                let seq = adaptive_sequence(&mut cache, k, num_of_tests, &mut rand);
+                (num_of_tests as f64 / opt_faults(&seq, k) as f64, k as f64)
+            },
+        }
+    });
+
+    for (chunk, &(c, _)) in ratios.chunks(ks.len()).zip(jobs.iter().step_by(ks.len())) {
+        let filename = format!("data/adversary_cache_{}.txt", CacheType::ALL[c].name());
+        let mut file = create_output(&filename, seed);
+        for (k, (ratio, bound)) in ks.iter().zip(chunk) {
+            let line = format!("{};{};{}\n", k, ratio, bound);
+            file.write_all(line.as_bytes()).unwrap();
+        }
+    }
+
+    // phases with several new pages separate RMA from the H_k-competitive
+    // algorithms, which uniform requests over k + 1 pages cannot
+    let randomized = (0..CacheType::ALL.len()).filter(|&c| CacheType::ALL[c].randomized()).collect::<Vec<usize>>();
+    let mut jobs = Vec::new();
+    for &c in randomized.iter() {
+        for &k in ks.iter() {
+            for new in [1, (k / 2).max(2)] {
+                jobs.push((c, k, new));
+            }
+        }
+    }
+
+    let ratios = par_map(&jobs, |&(c, k, new)| {
+        let (faults, opt) = faults_against_opt(CacheType::ALL[c], 2 * k, k, num_of_reps, seed, &[c as u64, k as u64, new as u64],
+            |rand| phase_sequence(k, new, num_of_tests, rand));
+        faults as f64 / opt as f64
+    });
+
+    for (ratios, jobs) in ratios.chunks(2 * ks.len()).zip(jobs.chunks(2 * ks.len())) {
+        let filename = format!("data/adversary_phases_cache_{}.txt", CacheType::ALL[jobs[0].0].name());
+        let mut file = create_output(&filename, seed);
+        for (ratio, &(_, k, new)) in ratios.iter().zip(jobs) {
+            let line = format!("{};{};{};{};{}\n", k, new, ratio, harmonic_number(k), 2.0 * harmonic_number(k) - 1.0);
+            file.write_all(line.as_bytes()).unwrap();
+        }
+    }
+}
+
+fn plot(path: &str, n: Option<usize>) {
+    let path = Path::new(path);
+    let rows = read_results(path).unwrap();
+    let n = n.unwrap_or_else(|| rows.iter().map(|row| row.n).max().unwrap());
+    let out_dir = path.parent().unwrap();
+    plot_by_k(&rows, n, out_dir).unwrap();
+    plot_by_n(&rows, out_dir).unwrap();
+}
+
+fn load_trace(path: &str, n: Option<usize>) -> (Trace, String) {
+    let path = Path::new(path);
+    let mut mapper = match n {
+        Some(n) => PageMapper::hashed(n),
+        None => PageMapper::dense(),
+    };
+    let trace = Trace::read(path, TraceFormat::from_path(path), &mut mapper).unwrap();
+    let name = path.file_stem().unwrap().to_string_lossy().into_owned();
+    (trace, name)
+}
+
+fn replay_trace(path: &str, n: Option<usize>, seed: u64) {
+    let (trace, name) = load_trace(path, n);
+    let ks = ((trace.n / 10).max(1)..=(trace.n / 5).max(1)).collect::<Vec<usize>>();
+    for (c, cache_type) in CacheType::ALL.into_iter().enumerate() {
+        let filename = format!("data/trace_{}cache_{}.txt", name, cache_type.name());
+        let mut file = create_output(&filename, seed);
+        for k in ks.iter() {
+            let mut rand = stream(seed, &[c as u64, *k as u64]);
+            let mut cache = Cache::new(trace.n, *k, cache_type);
+            let mut sum = 0;
+            for i in 0..trace.pages.len() {
+                sum += cache.access_at(trace.pages[i], trace.writes[i], trace.times[i], trace.ttls[i], &mut rand);
+            }
+            let avg = sum as f64 / trace.pages.len() as f64;
+
+            let line = format!("{};{}\n", k, avg);
+            file.write_all(line.as_bytes()).unwrap();
+        }
+    }
+}
+
+fn trace_stats(path: &str, k: usize, n: Option<usize>, seed: u64) {
+    let (trace, name) = load_trace(path, n);
+    println!("cache;hits;misses;compulsory;capacity;conflict;expired;evictions;write_backs;hit_ratio;phases;opt_lower_bound");
+    for (c, cache_type) in CacheType::ALL.into_iter().enumerate() {
+        let mut rand = stream(seed, &[c as u64, k as u64]);
+        let mut cache = Cache::new(trace.n, k, cache_type);
+        cache.enable_stats();
+        cache.enable_events();
+        for i in 0..trace.pages.len() {
+            cache.access_at(trace.pages[i], trace.writes[i], trace.times[i], trace.ttls[i], &mut rand);
+        }
+
+        let stats = cache.stats().unwrap();
+        println!("{};{};{};{};{};{};{};{};{};{};{};{}", cache_type.name(), stats.hits, stats.misses,
+            stats.compulsory_misses, stats.capacity_misses, stats.conflict_misses, stats.expired_misses,
+            stats.evictions, stats.write_backs, stats.hit_ratio(), stats.phases, stats.opt_lower_bound);
+
+        let filename = format!("data/trace_{}cache_{}_hits.txt", name, cache_type.name());
+        let mut file = create_output(&filename, seed);
+        for (page, hits) in stats.page_hits.iter().enumerate().skip(1) {
+            let line = format!("{};{}\n", page, hits);
+            file.write_all(line.as_bytes()).unwrap();
+        }
+
+        let filename = format!("data/trace_{}cache_{}_events.txt", name, cache_type.name());
+        let mut file = create_output(&filename, seed);
+        for event in cache.events().unwrap() {
+            file.write_all(event.to_line().as_bytes()).unwrap();
+        }
+    }
+}
+
+fn tenant_experiment(spec: &TenantSpec, seed: u64) {
+    let ns = spec.ns();
+    let partition = spec.partition();
+    let tenants = ns.len();
+    fs::create_dir_all(&spec.output_dir).unwrap();
+
+    // the same interleaved stream goes to every mode
+    let mut distributions = spec.distributions(seed);
+    let weights = WeightedIndex::new(spec.weights()).unwrap();
+    let mut chooser = stream(seed, &[tenants as u64, u64::MAX]);
+    let sequences = (0..spec.repetitions)
+        .map(|_| {
+            (0..spec.requests)
+                .map(|_| {
+                    let t = chooser.sample(&weights);
+                    (t, distributions[t].generate())
+                })
+                .collect::<Vec<(usize, usize)>>()
+        })
+        .collect::<Vec<Vec<(usize, usize)>>>();
+
+    // fault rate of each tenant with the whole cache to itself
+    let jobs = (0..spec.repetitions).flat_map(|r| (0..tenants).map(move |t| (r, t))).collect::<Vec<(usize, usize)>>();
+    let alone = par_map(&jobs, |&(r, t)| {
+        let mut rand = stream(seed, &[0, t as u64, r as u64]);
+        let mut cache = Cache::new(ns[t], spec.k, spec.policy);
+        let mut faults = 0;
+        let mut requests = 0;
+        for &(_, page) in sequences[r].iter().filter(|&&(u, _)| u == t) {
+            faults += cache.get_page(page, &mut rand);
+            requests += 1;
+        }
+        faults as f64 / requests.max(1) as f64
+    });
+
+    let jobs = (0..PartitionMode::ALL.len()).flat_map(|m| (0..spec.repetitions).map(move |r| (m, r))).collect::<Vec<(usize, usize)>>();
+    let outcomes = par_map(&jobs, |&(m, r)| {
+        let mut rand = stream(seed, &[1, m as u64, r as u64]);
+        let mut cache = TenantCache::new(PartitionMode::ALL[m], &ns, spec.k, &partition, spec.epoch, spec.policy);
+        for &(t, page) in sequences[r].iter() {
+            cache.get_page(t, page, &mut rand);
+        }
+        (cache.requests().to_vec(), cache.faults().to_vec())
+    });
+
+    let mut per_tenant = String::from("mode,tenant,repetition,requests,faults,fault_rate,alone_fault_rate,slowdown,seed\n");
+    let mut fairness = String::from("mode,repetition,fault_rate,jain_index,max_slowdown,seed\n");
+    for (&(m, r), (requests, faults)) in jobs.iter().zip(outcomes.iter()) {
+        let mode = PartitionMode::ALL[m].name();
+        let mut slowdowns = Vec::new();
+        for t in 0..tenants {
+            let rate = faults[t] as f64 / requests[t].max(1) as f64;
+            let alone_rate = alone[r * tenants + t];
+            // alone, a tenant faults at least once unless it made no request
+            let slowdown = (alone_rate > 0.0).then(|| rate / alone_rate);
+            per_tenant += &format!("{},{},{},{},{},{},{},{},{}\n", mode, t, r, requests[t], faults[t], rate,
+                alone_rate, slowdown.map_or(String::new(), |slowdown| slowdown.to_string()), seed);
+            slowdowns.extend(slowdown);
+        }
+        let rate = faults.iter().sum::<usize>() as f64 / spec.requests as f64;
+        let max_slowdown = slowdowns.iter().copied().fold(0.0, f64::max);
+        fairness += &format!("{},{},{},{},{},{}\n", mode, r, rate, jain_index(&slowdowns), max_slowdown, seed);
+    }
+
+    let output_dir = Path::new(&spec.output_dir);
+    fs::write(output_dir.join("tenants.csv"), per_tenant).unwrap();
+    fs::write(output_dir.join("fairness.csv"), fairness).unwrap();
+}
+
+/// Sweeps the default TTL, as a fraction of the trace's time span, to show
+/// how expiry trades off against capacity misses. "none" leaves only the
+/// TTLs the trace itself carries.
+fn trace_ttl(path: &str, k: usize, n: Option<usize>, seed: u64) {
+    let (trace, _) = load_trace(path, n);
+    let span = trace.span().max(1);
+    let ttls = [None, Some(span / 1000), Some(span / 100), Some(span / 10), Some(span)];
+
+    println!("cache;ttl;faults;fault_rate;compulsory;capacity;conflict;expired;expirations");
+    for (c, cache_type) in CacheType::ALL.into_iter().enumerate() {
+        for (t, ttl) in ttls.iter().enumerate() {
+            let mut rand = stream(seed, &[c as u64, t as u64, k as u64]);
+            let mut cache = Cache::new(trace.n, k, cache_type);
+            cache.set_ttl(*ttl);
+            cache.enable_stats();
+
+            let mut faults = 0;
+            for i in 0..trace.pages.len() {
+                faults += cache.access_at(trace.pages[i], trace.writes[i], trace.times[i], trace.ttls[i], &mut rand);
+            }
+
+            let stats = cache.stats().unwrap();
+            let ttl = ttl.map_or("none".to_owned(), |ttl| ttl.to_string());
+            println!("{};{};{};{};{};{};{};{};{}", cache_type.name(), ttl, faults,
+                faults as f64 / trace.pages.len() as f64, stats.compulsory_misses, stats.capacity_misses,
+                stats.conflict_misses, stats.expired_misses, cache.expirations());
+        }
+    }
+}
+
+type PrefetcherFactory = fn(usize) -> Option<Box<dyn Prefetcher>>;
+
+fn trace_prefetch(path: &str, k: usize, n: Option<usize>, seed: u64) {
+    let (trace, _) = load_trace(path, n);
+    let degree = 2;
+    let prefetchers: [PrefetcherFactory; 4] = [
+        |_| None,
+        |degree| Some(Box::new(Sequential::new(degree))),
+        |degree| Some(Box::new(Stride::new(degree))),
+        |degree| Some(Box::new(Markov::new(degree))),
+    ];
+
+    println!("cache;prefetcher;faults;fault_rate;issued;useful;wasted;pollution;accuracy");
+    for (c, cache_type) in CacheType::ALL.into_iter().enumerate() {
+        for (p, prefetcher) in prefetchers.iter().enumerate() {
+            let mut rand = stream(seed, &[c as u64, p as u64, k as u64]);
+            let mut cache = Cache::new(trace.n, k, cache_type);
+            let mut name = "NONE".to_owned();
+            if let Some(prefetcher) = prefetcher(degree) {
+                name = prefetcher.name().to_owned();
+                cache.set_prefetcher(prefetcher);
+            }
+
+            let mut faults = 0;
+            for page in trace.pages.iter() {
+                faults += cache.get_page(*page, &mut rand);
+            }
+
+            let stats = cache.prefetch_stats().cloned().unwrap_or_default();
+            println!("{};{};{};{};{};{};{};{};{}", cache_type.name(), name, faults,
+                faults as f64 / trace.pages.len() as f64, stats.issued, stats.useful,
+                stats.wasted, stats.pollution, stats.accuracy());
+        }
     }
 }
 
 fn main() {
     let now = std::time::Instant::now();
-    experiment();
+    let mut args = std::env::args().skip(1).collect::<Vec<String>>();
+    let cli_seed: Option<u64> = args.iter().position(|arg| arg == "--seed").map(|i| {
+        args.remove(i);
+        args.remove(i).parse().unwrap()
+    });
+
+    if let command @ (Some("run") | Some("augment") | None) = args.first().map(String::as_str) {
+        let spec = match args.get(1) {
+            Some(path) => ExperimentSpec::read(Path::new(path)).unwrap(),
+            None => ExperimentSpec::lab(),
+        };
+        let seed = cli_seed.or(spec.seed).unwrap_or_else(rand::random);
+        println!("Seed: {}", seed);
+        match command {
+            Some("augment") => augmentation_experiment(&spec, seed),
+            _ => experiment(&spec, seed),
+        }
+        println!("Time elapsed: {} s", now.elapsed().as_secs_f64());
+        return;
+    }
+
+    let seed = || {
+        let seed = cli_seed.unwrap_or_else(rand::random);
+        println!("Seed: {}", seed);
+        seed
+    };
+    match args[0].as_str() {
+        "mrc" => mrc_experiment(seed()),
+        "adversary" => adversary_experiment(seed()),
+        "plot" => {
+            let path = args.get(1).map_or("data/results.csv", String::as_str);
+            let n = args.get(2).map(|n| n.parse().unwrap());
+            plot(path, n)
+        },
+        "replay" => {
+            let path = args.get(1).expect("usage: lab2 replay <trace> [n]");
+            let n = args.get(2).map(|n| n.parse().unwrap());
+            replay_trace(path, n, seed())
+        },
+        "prefetch" => {
+            let usage = "usage: lab2 prefetch <trace> <k> [n]";
+            let path = args.get(1).expect(usage);
+            let k = args.get(2).expect(usage).parse().unwrap();
+            let n = args.get(3).map(|n| n.parse().unwrap());
+            trace_prefetch(path, k, n, seed())
+        },
+        "tenants" => {
+            let path = args.get(1).expect("usage: lab2 tenants <spec>");
+            let spec = TenantSpec::read(Path::new(path)).unwrap();
+            let seed = cli_seed.or(spec.seed).unwrap_or_else(rand::random);
+            println!("Seed: {}", seed);
+            tenant_experiment(&spec, seed)
+        },
+        "ttl" => {
+            let usage = "usage: lab2 ttl <trace> <k> [n]";
+            let path = args.get(1).expect(usage);
+            let k = args.get(2).expect(usage).parse().unwrap();
+            let n = args.get(3).map(|n| n.parse().unwrap());
+            trace_ttl(path, k, n, seed())
+        },
+        "stats" => {
+            let usage = "usage: lab2 stats <trace> <k> [n]";
+            let path = args.get(1).expect(usage);
+            let k = args.get(2).expect(usage).parse().unwrap();
+            let n = args.get(3).map(|n| n.parse().unwrap());
+            trace_stats(path, k, n, seed())
+        },
+        command => panic!("unknown command {:?}", command),
+    }
     println!("Time elapsed: {} s", now.elapsed().as_secs_f64());
 }
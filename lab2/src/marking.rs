@@ -0,0 +1,156 @@
+use crate::policy::*;
+use rand::{Rng, RngCore};
+use std::collections::HashSet;
+
+/// Splits a request sequence into k-phases: maximal runs with at most k
+/// distinct pages. A page is new in a phase if the previous phase did not
+/// request it; OPT faults at least half the total number of new pages.
+pub struct PhaseTracker {
+    k         : usize,
+    current   : HashSet<usize>,
+    previous  : HashSet<usize>,
+    new_pages : Vec<usize>, // per phase
+}
+
+impl PhaseTracker {
+    pub fn new(k: usize) -> Self {
+        PhaseTracker {
+            k,
+            current   : HashSet::new(),
+            previous  : HashSet::new(),
+            new_pages : Vec::new(),
+        }
+    }
+
+    pub fn request(&mut self, page: usize) {
+        if self.current.contains(&page) {
+            return;
+        }
+        if self.new_pages.is_empty() || self.current.len() == self.k {
+            self.previous = std::mem::take(&mut self.current);
+            self.new_pages.push(0);
+        }
+        self.current.insert(page);
+        if !self.previous.contains(&page) {
+            *self.new_pages.last_mut().unwrap() += 1;
+        }
+    }
+
+    pub fn phases(&self) -> usize {
+        self.new_pages.len()
+    }
+
+    pub fn new_pages(&self) -> &[usize] {
+        &self.new_pages
+    }
+
+    /// Lower bound on the number of faults of the optimal offline cache.
+    pub fn opt_lower_bound(&self) -> usize {
+        self.new_pages.iter().sum::<usize>().div_ceil(2)
+    }
+}
+
+/// Chooses which unmarked pages a marking algorithm evicts on a fault.
+pub trait UnmarkedRule {
+    fn name(&self) -> &str;
+
+    fn on_request(&mut self, _page: usize) {}
+
+    /// `unmarked` is never empty.
+    fn choose(&mut self, unmarked: &[usize], rand: &mut dyn RngCore, victims: &mut Vec<usize>);
+}
+
+/// Evicts every unmarked page, which makes the marking algorithm FWF.
+#[derive(Default)]
+pub struct FlushUnmarked;
+
+impl UnmarkedRule for FlushUnmarked {
+    fn name(&self) -> &str {
+        "FWF"
+    }
+
+    fn choose(&mut self, unmarked: &[usize], _rand: &mut dyn RngCore, victims: &mut Vec<usize>) {
+        victims.extend_from_slice(unmarked);
+    }
+}
+
+/// Evicts a uniformly random unmarked page, which makes the marking
+/// algorithm RMA.
+#[derive(Default)]
+pub struct RandomUnmarked;
+
+impl UnmarkedRule for RandomUnmarked {
+    fn name(&self) -> &str {
+        "RMA"
+    }
+
+    fn choose(&mut self, unmarked: &[usize], rand: &mut dyn RngCore, victims: &mut Vec<usize>) {
+        victims.push(unmarked[rand.gen_range(0..unmarked.len())]);
+    }
+}
+
+/// Generic marking algorithm: every requested page gets marked, a fault on
+/// a full cache with all pages marked starts a new phase and unmarks them,
+/// and victims are always picked among the unmarked pages by `R`.
+pub struct Marking<R: UnmarkedRule> {
+    rule     : R,
+    marked   : Vec<bool>, // indexed by page
+    unmarked : Vec<usize>,
+    phases   : usize,
+}
+
+impl<R: UnmarkedRule> Marking<R> {
+    pub fn new(n: usize, rule: R) -> Self {
+        Marking {
+            rule,
+            marked   : vec![false; n + 1],
+            unmarked : Vec::new(),
+            phases   : 0,
+        }
+    }
+
+    /// Number of phases started so far, counting the first one.
+    pub fn phases(&self) -> usize {
+        self.phases
+    }
+
+    fn mark(&mut self, page: usize) {
+        if self.phases == 0 {
+            self.phases = 1;
+        }
+        self.marked[page] = true;
+        self.rule.on_request(page);
+    }
+}
+
+impl<R: UnmarkedRule> ReplacementPolicy for Marking<R> {
+    fn name(&self) -> &str {
+        self.rule.name()
+    }
+
+    fn on_hit(&mut self, page: usize) {
+        self.mark(page);
+    }
+
+    fn on_insert(&mut self, page: usize) {
+        self.mark(page);
+    }
+
+    fn on_prefetch(&mut self, _page: usize) {}
+
+    fn choose_victims(&mut self, resident: &PageSet, _dirty: &PageSet, _page: usize, rand: &mut dyn RngCore, victims: &mut Vec<usize>) {
+        if resident.iter().all(|&v| self.marked[v]) {
+            for &v in resident.iter() {
+                self.marked[v] = false;
+            }
+            self.phases += 1;
+        }
+
+        self.unmarked.clear();
+        self.unmarked.extend(resident.iter().copied().filter(|&v| !self.marked[v]));
+        self.rule.choose(&self.unmarked, rand, victims);
+    }
+}
+
+pub type Fwf = Marking<FlushUnmarked>;
+pub type Rma = Marking<RandomUnmarked>;
@@ -0,0 +1,97 @@
+use std::collections::{BTreeSet, HashMap};
+
+/// Number of faults of Belady's optimal offline cache of size k, which
+/// always evicts the page whose next request is furthest in the future.
+pub fn opt_faults(seq: &[usize], k: usize) -> usize {
+    assert!(k > 0, "OPT needs at least one page of cache");
+    // next_use[i] is the position of the next request for seq[i]
+    let mut next_use = vec![usize::MAX; seq.len()];
+    let mut seen: HashMap<usize, usize> = HashMap::new();
+    for (i, &page) in seq.iter().enumerate().rev() {
+        if let Some(&j) = seen.get(&page) {
+            next_use[i] = j;
+        }
+        seen.insert(page, i);
+    }
+
+    // resident pages keyed by (next use, page), current next use per page
+    let mut by_next: BTreeSet<(usize, usize)> = BTreeSet::new();
+    let mut resident: HashMap<usize, usize> = HashMap::new();
+    let mut faults = 0;
+    for (i, &page) in seq.iter().enumerate() {
+        match resident.get(&page) {
+            Some(&next) => {
+                by_next.remove(&(next, page));
+            },
+            None => {
+                faults += 1;
+                if resident.len() == k {
+                    let (_, victim) = by_next.pop_last().unwrap();
+                    resident.remove(&victim);
+                }
+            }
+        }
+        resident.insert(page, next_use[i]);
+        by_next.insert((next_use[i], page));
+    }
+    faults
+}
+
+/// Sleator and Tarjan's resource augmentation bound: LRU or FIFO with
+/// k pages faults at most k / (k - h + 1) times as often as OPT with h.
+pub fn augmentation_bound(k: usize, h: usize) -> f64 {
+    assert!(0 < h && h <= k, "OPT must not have more pages than the online cache");
+    k as f64 / (k - h + 1) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::SimRng;
+    use rand::{Rng, SeedableRng};
+
+    /// Fewest faults by trying every victim on every fault of a full cache.
+    fn brute_force(seq: &[usize], k: usize, resident: &mut Vec<usize>) -> usize {
+        let Some((&page, rest)) = seq.split_first() else { return 0 };
+        if resident.contains(&page) {
+            return brute_force(rest, k, resident);
+        }
+        if resident.len() < k {
+            resident.push(page);
+            let faults = brute_force(rest, k, resident);
+            resident.pop();
+            return 1 + faults;
+        }
+        let mut best = usize::MAX;
+        for i in 0..k {
+            let victim = std::mem::replace(&mut resident[i], page);
+            best = best.min(brute_force(rest, k, resident));
+            resident[i] = victim;
+        }
+        1 + best
+    }
+
+    #[test]
+    fn evicts_the_furthest_next_request() {
+        // LRU faults on all 7 requests
+        assert_eq!(opt_faults(&[1, 2, 3, 1, 2, 3, 1], 2), 5);
+        assert_eq!(opt_faults(&[1, 2, 3, 4, 1, 2, 5, 1, 2, 3, 4, 5], 3), 7);
+    }
+
+    #[test]
+    fn random_sequences_match_brute_force() {
+        let mut rand = SimRng::seed_from_u64(34);
+        for _ in 0..300 {
+            let n = rand.gen_range(1..=6);
+            let k = rand.gen_range(1..=4);
+            let seq = (0..rand.gen_range(0..=12)).map(|_| rand.gen_range(1..=n)).collect::<Vec<usize>>();
+            assert_eq!(opt_faults(&seq, k), brute_force(&seq, k, &mut Vec::new()), "k = {} on {:?}", k, seq);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "at least one page")]
+    fn rejects_an_empty_cache() {
+        opt_faults(&[1, 2], 0);
+    }
+}
@@ -0,0 +1,44 @@
+/// Two-sided 95% Student t quantiles for 1..=30 degrees of freedom.
+const T_95: [f64; 30] = [
+    12.706, 4.303, 3.182, 2.776, 2.571, 2.447, 2.365, 2.306, 2.262, 2.228,
+    2.201, 2.179, 2.160, 2.145, 2.131, 2.120, 2.110, 2.101, 2.093, 2.086,
+    2.080, 2.074, 2.069, 2.064, 2.060, 2.056, 2.052, 2.048, 2.045, 2.042,
+];
+
+fn t_quantile(df: usize) -> f64 {
+    match df {
+        0 => f64::NAN,
+        1..=30 => T_95[df - 1],
+        _ => 1.96,
+    }
+}
+
+/// Mean of the per-repetition differences a - b with a 95% confidence
+/// interval. Pairing removes the sequence-to-sequence noise shared by both.
+pub struct PairedDiff {
+    pub mean       : f64,
+    pub half_width : f64,
+}
+
+impl PairedDiff {
+    pub fn new(a: &[f64], b: &[f64]) -> Self {
+        assert_eq!(a.len(), b.len(), "paired samples must have equal length");
+        let reps = a.len();
+        let diffs = a.iter().zip(b).map(|(x, y)| x - y).collect::<Vec<f64>>();
+        let mean = diffs.iter().sum::<f64>() / reps as f64;
+        let var = diffs.iter().map(|d| (d - mean) * (d - mean)).sum::<f64>() / (reps as f64 - 1.0);
+
+        PairedDiff {
+            mean,
+            half_width: t_quantile(reps - 1) * (var / reps as f64).sqrt(),
+        }
+    }
+
+    pub fn low(&self) -> f64 {
+        self.mean - self.half_width
+    }
+
+    pub fn high(&self) -> f64 {
+        self.mean + self.half_width
+    }
+}
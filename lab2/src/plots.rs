@@ -0,0 +1,121 @@
+use crate::results::*;
+use plotters::prelude::*;
+use std::path::Path;
+
+type Series = (String, Vec<(f32, f32)>);
+
+/// Labels in order of first appearance.
+fn distinct<F: Fn(&ResultRow) -> &str>(rows: &[ResultRow], key: F) -> Vec<String> {
+    let mut labels: Vec<String> = Vec::new();
+    for row in rows {
+        if !labels.iter().any(|label| label == key(row)) {
+            labels.push(key(row).to_owned());
+        }
+    }
+    labels
+}
+
+fn draw_chart(out_file_name: &str, caption: &str, x_desc: &str, series: &[Series]) -> Result<(), Box<dyn std::error::Error>> {
+    let colors = [RED, BLUE, GREEN, YELLOW, MAGENTA, CYAN];
+    let points = || series.iter().flat_map(|(_, points)| points.iter());
+    let min_x = points().map(|p| p.0).fold(f32::INFINITY, f32::min);
+    let max_x = points().map(|p| p.0).fold(f32::NEG_INFINITY, f32::max);
+    let max_y = points().map(|p| p.1).fold(0.0, f32::max);
+
+    let root = BitMapBackend::new(out_file_name, (1024, 768)).into_drawing_area();
+    root.fill(&WHITE)?;
+
+    let mut chart = ChartBuilder::on(&root)
+        .x_label_area_size(35)
+        .y_label_area_size(40)
+        .margin(20)
+        .caption(caption, ("sans-serif", 30.0).into_font())
+        .build_cartesian_2d(min_x..max_x, 0.0f32..max_y * 1.05)?;
+
+    chart
+        .configure_mesh()
+        .disable_x_mesh()
+        .disable_y_mesh()
+        .x_desc(x_desc)
+        .y_desc("Avg cost")
+        .draw()?;
+
+    for (idx, (label, points)) in series.iter().enumerate() {
+        let color = colors[idx % colors.len()];
+        chart
+            .draw_series(LineSeries::new(points.iter().copied(), color.stroke_width(2)))?
+            .label(label)
+            .legend(move |(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], color));
+    }
+
+    chart
+        .configure_series_labels()
+        .background_style(WHITE.mix(0.8))
+        .border_style(BLACK)
+        .draw()?;
+
+    root.present()?;
+    println!("Result has been saved to {}", out_file_name);
+    Ok(())
+}
+
+/// Fault rate against k for a fixed n, one chart per distribution with a
+/// line per policy.
+pub fn plot_by_k(rows: &[ResultRow], n: usize, out_dir: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    let rows = rows.iter().filter(|row| row.n == n).cloned().collect::<Vec<ResultRow>>();
+    let mut ks = rows.iter().map(|row| row.k).collect::<Vec<usize>>();
+    ks.sort_unstable();
+    ks.dedup();
+
+    for dist in distinct(&rows, |row| &row.distribution) {
+        let series = distinct(&rows, |row| &row.policy)
+            .into_iter()
+            .map(|policy| {
+                let points = ks
+                    .iter()
+                    .filter_map(|&k| {
+                        mean_fault_rate(&rows, |row| row.k == k && row.distribution == dist && row.policy == policy)
+                            .map(|rate| (k as f32, rate as f32))
+                    })
+                    .collect();
+                (format!("Cache = {}", policy), points)
+            })
+            .collect::<Vec<Series>>();
+
+        let out_file_name = out_dir.join(format!("{}.png", dist));
+        let caption = format!("Avg cost by k for {} distribution (n = {})", dist, n);
+        draw_chart(&out_file_name.to_string_lossy(), &caption, "k", &series)?;
+    }
+    Ok(())
+}
+
+/// Fault rate against n at the largest k tried for each n, one chart per
+/// policy with a line per distribution.
+pub fn plot_by_n(rows: &[ResultRow], out_dir: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    let mut ns = rows.iter().map(|row| row.n).collect::<Vec<usize>>();
+    ns.sort_unstable();
+    ns.dedup();
+    let max_k = |n: usize| rows.iter().filter(|row| row.n == n).map(|row| row.k).max().unwrap();
+
+    for policy in distinct(rows, |row| &row.policy) {
+        let series = distinct(rows, |row| &row.distribution)
+            .into_iter()
+            .map(|dist| {
+                let points = ns
+                    .iter()
+                    .filter_map(|&n| {
+                        let k = max_k(n);
+                        mean_fault_rate(rows, |row| row.n == n && row.k == k && row.distribution == dist && row.policy == policy)
+                            .map(|rate| (n as f32, rate as f32))
+                    })
+                    .collect();
+                (format!("Distribution = {}", dist), points)
+            })
+            .collect::<Vec<Series>>();
+
+        let out_file_name = out_dir.join(format!("{}.png", policy));
+        let caption = format!("Avg cost by n for {} cache (largest k)", policy);
+        draw_chart(&out_file_name.to_string_lossy(), &caption, "n", &series)?;
+    }
+    Ok(())
+}
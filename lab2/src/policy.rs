@@ -0,0 +1,235 @@
+use rand::{Rng, RngCore};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashSet, VecDeque};
+use std::hash::BuildHasherDefault;
+
+/// Set of resident pages. Uses a fixed hasher so that iteration order, and
+/// with it every seeded run, is reproducible.
+pub type PageSet = HashSet<usize, BuildHasherDefault<DefaultHasher>>;
+
+/// Eviction rule driven by a `Cache`.
+///
+/// The cache owns the set of resident pages and calls back into the policy
+/// on every hit and insert. Only when the cache is full does it ask the
+/// policy which resident pages to give up for the incoming one.
+pub trait ReplacementPolicy {
+    fn name(&self) -> &str;
+
+    fn on_hit(&mut self, _page: usize) {}
+
+    fn on_insert(&mut self, _page: usize) {}
+
+    /// Called instead of `on_insert` for a page the cache fetched on its
+    /// own. Not a request, so it should not count as a use of the page;
+    /// by default it is an insert all the same.
+    fn on_prefetch(&mut self, page: usize) {
+        self.on_insert(page);
+    }
+
+    /// Called after a write request, once the page is resident.
+    fn on_write(&mut self, _page: usize) {}
+
+    /// Called when the cache drops a page on its own, e.g. on expiry.
+    fn on_remove(&mut self, _page: usize) {}
+
+    /// Called when the cache size changes to `k`, before any page is
+    /// evicted to fit it.
+    fn on_resize(&mut self, _k: usize) {}
+
+    /// Pushes to `victims` the resident pages to evict before `page`
+    /// is inserted. Called only when the cache is full, or with page 0
+    /// when it shrinks. To make room for a prefetch, `page` is the page
+    /// just requested, resident but left out of `resident` so that it
+    /// stays. `dirty` holds the pages written since they came in.
+    fn choose_victims(
+        &mut self,
+        resident : &PageSet,
+        dirty    : &PageSet,
+        page     : usize,
+        rand     : &mut dyn RngCore,
+        victims  : &mut Vec<usize>,
+    );
+}
+
+impl<P: ReplacementPolicy + ?Sized> ReplacementPolicy for Box<P> {
+    fn name(&self) -> &str {
+        (**self).name()
+    }
+
+    fn on_hit(&mut self, page: usize) {
+        (**self).on_hit(page)
+    }
+
+    fn on_insert(&mut self, page: usize) {
+        (**self).on_insert(page)
+    }
+
+    fn on_prefetch(&mut self, page: usize) {
+        (**self).on_prefetch(page)
+    }
+
+    fn on_write(&mut self, page: usize) {
+        (**self).on_write(page)
+    }
+
+    fn on_remove(&mut self, page: usize) {
+        (**self).on_remove(page)
+    }
+
+    fn on_resize(&mut self, k: usize) {
+        (**self).on_resize(k)
+    }
+
+    fn choose_victims(&mut self, resident: &PageSet, dirty: &PageSet, page: usize, rand: &mut dyn RngCore, victims: &mut Vec<usize>) {
+        (**self).choose_victims(resident, dirty, page, rand, victims)
+    }
+}
+
+#[derive(Default)]
+pub struct Fifo {
+    queue: VecDeque<usize>, // pages in order of arrival
+}
+
+impl ReplacementPolicy for Fifo {
+    fn name(&self) -> &str {
+        "FIFO"
+    }
+
+    fn on_insert(&mut self, page: usize) {
+        self.queue.push_back(page);
+    }
+
+    fn on_remove(&mut self, page: usize) {
+        self.queue.retain(|&p| p != page);
+    }
+
+    // the oldest page may be one the cache keeps
+    fn choose_victims(&mut self, resident: &PageSet, _dirty: &PageSet, _page: usize, _rand: &mut dyn RngCore, victims: &mut Vec<usize>) {
+        if let Some(i) = self.queue.iter().position(|page| resident.contains(page)) {
+            victims.extend(self.queue.remove(i));
+        }
+    }
+}
+
+pub struct Lru {
+    counter  : usize,
+    last_use : Vec<usize>, // indexed by page
+}
+
+impl Lru {
+    pub fn new(n: usize) -> Self {
+        Lru {
+            counter  : 0,
+            last_use : vec![0; n + 1],
+        }
+    }
+
+    fn update(&mut self, page: usize) {
+        self.counter += 1;
+        self.last_use[page] = self.counter;
+    }
+}
+
+impl ReplacementPolicy for Lru {
+    fn name(&self) -> &str {
+        "LRU"
+    }
+
+    fn on_hit(&mut self, page: usize) {
+        self.update(page);
+    }
+
+    fn on_insert(&mut self, page: usize) {
+        self.update(page);
+    }
+
+    fn choose_victims(&mut self, resident: &PageSet, _dirty: &PageSet, _page: usize, _rand: &mut dyn RngCore, victims: &mut Vec<usize>) {
+        victims.extend(resident.iter().copied().min_by_key(|&v| self.last_use[v]));
+    }
+}
+
+/// LRU that prefers clean pages: evicts the least recently used clean
+/// page and falls back to plain LRU only when every page is dirty.
+pub struct LruCleanFirst {
+    lru : Lru,
+}
+
+impl LruCleanFirst {
+    pub fn new(n: usize) -> Self {
+        LruCleanFirst {
+            lru : Lru::new(n),
+        }
+    }
+}
+
+impl ReplacementPolicy for LruCleanFirst {
+    fn name(&self) -> &str {
+        "LRU-CF"
+    }
+
+    fn on_hit(&mut self, page: usize) {
+        self.lru.update(page);
+    }
+
+    fn on_insert(&mut self, page: usize) {
+        self.lru.update(page);
+    }
+
+    fn choose_victims(&mut self, resident: &PageSet, dirty: &PageSet, _page: usize, _rand: &mut dyn RngCore, victims: &mut Vec<usize>) {
+        let last_use = &self.lru.last_use;
+        let victim = resident
+            .iter()
+            .copied()
+            .filter(|v| !dirty.contains(v))
+            .min_by_key(|&v| last_use[v])
+            .or_else(|| resident.iter().copied().min_by_key(|&v| last_use[v]));
+        victims.extend(victim);
+    }
+}
+
+/// Perfect LFU: frequencies survive eviction.
+pub struct Lfu {
+    uses: Vec<usize>, // indexed by page
+}
+
+impl Lfu {
+    pub fn new(n: usize) -> Self {
+        Lfu {
+            uses: vec![0; n + 1],
+        }
+    }
+}
+
+impl ReplacementPolicy for Lfu {
+    fn name(&self) -> &str {
+        "LFU"
+    }
+
+    fn on_hit(&mut self, page: usize) {
+        self.uses[page] += 1;
+    }
+
+    fn on_insert(&mut self, page: usize) {
+        self.uses[page] += 1;
+    }
+
+    fn on_prefetch(&mut self, _page: usize) {}
+
+    fn choose_victims(&mut self, resident: &PageSet, _dirty: &PageSet, _page: usize, _rand: &mut dyn RngCore, victims: &mut Vec<usize>) {
+        victims.extend(resident.iter().copied().min_by_key(|&v| self.uses[v]));
+    }
+}
+
+#[derive(Default)]
+pub struct Random;
+
+impl ReplacementPolicy for Random {
+    fn name(&self) -> &str {
+        "RAND"
+    }
+
+    fn choose_victims(&mut self, resident: &PageSet, _dirty: &PageSet, _page: usize, rand: &mut dyn RngCore, victims: &mut Vec<usize>) {
+        let rand_idx = rand.gen_range(0..resident.len());
+        victims.extend(resident.iter().copied().nth(rand_idx));
+    }
+}
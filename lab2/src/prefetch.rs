@@ -0,0 +1,185 @@
+use crate::policy::*;
+use std::collections::HashMap;
+
+/// Suggests pages to bring into the cache ahead of demand.
+pub trait Prefetcher {
+    fn name(&self) -> &str;
+
+    /// Called after every demand request. Pushes to `pages` the pages
+    /// worth prefetching; the cache skips ones already resident or
+    /// outside 1..=n.
+    fn on_request(&mut self, page: usize, hit: bool, pages: &mut Vec<usize>);
+}
+
+/// Next-N sequential prefetch on a miss.
+pub struct Sequential {
+    degree: usize,
+}
+
+impl Sequential {
+    pub fn new(degree: usize) -> Self {
+        Sequential { degree }
+    }
+}
+
+impl Prefetcher for Sequential {
+    fn name(&self) -> &str {
+        "SEQ"
+    }
+
+    fn on_request(&mut self, page: usize, hit: bool, pages: &mut Vec<usize>) {
+        if !hit {
+            pages.extend((1..=self.degree).map(|i| page + i));
+        }
+    }
+}
+
+/// Prefetches along a stride once it has been seen twice in a row.
+pub struct Stride {
+    degree : usize,
+    last   : Option<usize>,
+    stride : Option<isize>,
+}
+
+impl Stride {
+    pub fn new(degree: usize) -> Self {
+        Stride {
+            degree,
+            last   : None,
+            stride : None,
+        }
+    }
+}
+
+impl Prefetcher for Stride {
+    fn name(&self) -> &str {
+        "STRIDE"
+    }
+
+    fn on_request(&mut self, page: usize, _hit: bool, pages: &mut Vec<usize>) {
+        let stride = self.last.map(|last| page as isize - last as isize);
+        if let Some(stride) = stride.filter(|&s| s != 0 && Some(s) == self.stride) {
+            pages.extend(
+                (1..=self.degree as isize)
+                    .map(|i| page as isize + i * stride)
+                    .filter(|&p| p > 0)
+                    .map(|p| p as usize),
+            );
+        }
+        self.stride = stride;
+        self.last = Some(page);
+    }
+}
+
+/// First-order Markov prefetch: the most frequent successors of the
+/// current page in the history so far.
+pub struct Markov {
+    degree     : usize,
+    last       : Option<usize>,
+    successors : HashMap<usize, HashMap<usize, usize>>,
+}
+
+impl Markov {
+    pub fn new(degree: usize) -> Self {
+        Markov {
+            degree,
+            last       : None,
+            successors : HashMap::new(),
+        }
+    }
+}
+
+impl Prefetcher for Markov {
+    fn name(&self) -> &str {
+        "MARKOV"
+    }
+
+    fn on_request(&mut self, page: usize, _hit: bool, pages: &mut Vec<usize>) {
+        if let Some(last) = self.last {
+            *self.successors.entry(last).or_default().entry(page).or_insert(0) += 1;
+        }
+        self.last = Some(page);
+
+        if let Some(next) = self.successors.get(&page) {
+            let mut next = next.iter().map(|(&p, &count)| (count, p)).collect::<Vec<(usize, usize)>>();
+            // most frequent first, ties towards lower pages for reproducibility
+            next.sort_unstable_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+            pages.extend(next.into_iter().take(self.degree).map(|(_, p)| p));
+        }
+    }
+}
+
+/// Prefetch counters. A prefetch is useful if the page is demanded before
+/// it leaves the cache and wasted if it is evicted first. Pollution counts
+/// demand misses on pages that a prefetch pushed out.
+#[derive(Clone, Default)]
+pub struct PrefetchStats {
+    pub issued    : usize,
+    pub useful    : usize,
+    pub wasted    : usize,
+    pub pollution : usize,
+}
+
+impl PrefetchStats {
+    pub fn accuracy(&self) -> f64 {
+        if self.issued == 0 {
+            return 0.0;
+        }
+        self.useful as f64 / self.issued as f64
+    }
+}
+
+/// Prefetcher together with the bookkeeping behind `PrefetchStats`.
+pub struct PrefetchState {
+    prefetcher : Box<dyn Prefetcher>,
+    pending    : PageSet, // prefetched and not demanded yet
+    displaced  : PageSet, // evicted to make room for a prefetch
+    stats      : PrefetchStats,
+}
+
+impl PrefetchState {
+    pub fn new(prefetcher: Box<dyn Prefetcher>) -> Self {
+        PrefetchState {
+            prefetcher,
+            pending   : PageSet::default(),
+            displaced : PageSet::default(),
+            stats     : PrefetchStats::default(),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        self.prefetcher.name()
+    }
+
+    pub fn stats(&self) -> &PrefetchStats {
+        &self.stats
+    }
+
+    pub fn on_demand(&mut self, page: usize, hit: bool) {
+        if hit && self.pending.remove(&page) {
+            self.stats.useful += 1;
+        }
+        if !hit && self.displaced.remove(&page) {
+            self.stats.pollution += 1;
+        }
+    }
+
+    pub fn on_evict(&mut self, victim: usize, by_prefetch: bool) {
+        if self.pending.remove(&victim) {
+            self.stats.wasted += 1;
+        }
+        if by_prefetch {
+            self.displaced.insert(victim);
+        }
+    }
+
+    pub fn on_prefetch(&mut self, page: usize) {
+        self.stats.issued += 1;
+        self.pending.insert(page);
+        self.displaced.remove(&page);
+    }
+
+    pub fn candidates(&mut self, page: usize, hit: bool, pages: &mut Vec<usize>) {
+        self.prefetcher.on_request(page, hit, pages);
+    }
+}
@@ -0,0 +1,85 @@
+use std::fs::File;
+use std::io::{self, prelude::*, BufReader, BufWriter};
+use std::path::Path;
+
+pub const RESULTS_HEADER: &str = "n,k,distribution,policy,repetition,requests,faults,fault_rate,write_backs,cost,seed";
+
+/// One repetition of one policy on one (n, k, distribution) cell,
+/// as a row of the tidy results table.
+#[derive(Clone)]
+pub struct ResultRow {
+    pub n            : usize,
+    pub k            : usize,
+    pub distribution : String,
+    pub policy       : String,
+    pub repetition   : usize,
+    pub requests     : usize,
+    pub faults       : usize,
+    pub write_backs  : usize,
+    pub cost         : f64,
+    pub seed         : u64,
+}
+
+impl ResultRow {
+    pub fn fault_rate(&self) -> f64 {
+        self.faults as f64 / self.requests as f64
+    }
+
+    pub fn to_line(&self) -> String {
+        format!("{},{},{},{},{},{},{},{},{},{},{}\n", self.n, self.k, self.distribution, self.policy,
+            self.repetition, self.requests, self.faults, self.fault_rate(), self.write_backs, self.cost, self.seed)
+    }
+
+    pub fn parse(line: &str) -> Option<Self> {
+        let fields = line.trim().split(',').collect::<Vec<&str>>();
+        if fields.len() != 11 {
+            return None;
+        }
+        Some(ResultRow {
+            n            : fields[0].parse().ok()?,
+            k            : fields[1].parse().ok()?,
+            distribution : fields[2].to_owned(),
+            policy       : fields[3].to_owned(),
+            repetition   : fields[4].parse().ok()?,
+            requests     : fields[5].parse().ok()?,
+            faults       : fields[6].parse().ok()?,
+            write_backs  : fields[8].parse().ok()?,
+            cost         : fields[9].parse().ok()?,
+            seed         : fields[10].parse().ok()?,
+        })
+    }
+}
+
+pub fn write_results(path: &Path, rows: &[ResultRow]) -> io::Result<()> {
+    let mut file = BufWriter::new(File::create(path)?);
+    writeln!(file, "{}", RESULTS_HEADER)?;
+    for row in rows {
+        file.write_all(row.to_line().as_bytes())?;
+    }
+    file.flush()
+}
+
+pub fn read_results(path: &Path) -> io::Result<Vec<ResultRow>> {
+    let reader = BufReader::new(File::open(path)?);
+    let mut rows = Vec::new();
+    for (i, line) in reader.lines().enumerate().skip(1) {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let row = ResultRow::parse(&line).ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidData, format!("line {}: malformed result row", i + 1))
+        })?;
+        rows.push(row);
+    }
+    Ok(rows)
+}
+
+/// Mean fault rate over repetitions of every row matching the filter.
+pub fn mean_fault_rate<F: Fn(&ResultRow) -> bool>(rows: &[ResultRow], filter: F) -> Option<f64> {
+    let rates = rows.iter().filter(|row| filter(row)).map(ResultRow::fault_rate).collect::<Vec<f64>>();
+    if rates.is_empty() {
+        return None;
+    }
+    Some(rates.iter().sum::<f64>() / rates.len() as f64)
+}
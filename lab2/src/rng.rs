@@ -0,0 +1,24 @@
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+/// Generator used wherever a run has to be reproducible from a seed.
+pub type SimRng = StdRng;
+
+/// One SplitMix64 step: a bijection on u64 that spreads nearby inputs
+/// over the whole range.
+pub fn splitmix(mut x: u64) -> u64 {
+    x = x.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    x = (x ^ (x >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    x ^ (x >> 31)
+}
+
+/// Derives an independent seed for the job identified by `ids`
+/// (e.g. n, distribution, policy, k) from the master seed.
+pub fn derive_seed(master: u64, ids: &[u64]) -> u64 {
+    ids.iter().fold(splitmix(master), |seed, &id| splitmix(seed ^ splitmix(id)))
+}
+
+pub fn stream(master: u64, ids: &[u64]) -> SimRng {
+    SimRng::seed_from_u64(derive_seed(master, ids))
+}
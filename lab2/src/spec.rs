@@ -0,0 +1,315 @@
+use crate::cache::*;
+use crate::distribution::*;
+use crate::rng::*;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// Cache sizes to try for a universe of n pages.
+#[derive(Clone, Deserialize)]
+#[serde(untagged)]
+pub enum KRange {
+    /// Every k in n / from_divisor..=n / to_divisor.
+    Divisors { from_divisor: usize, to_divisor: usize },
+    List(Vec<usize>),
+}
+
+impl KRange {
+    pub fn ks(&self, n: usize) -> Vec<usize> {
+        match self {
+            KRange::Divisors { from_divisor, to_divisor } => ((n / from_divisor)..=(n / to_divisor)).collect(),
+            KRange::List(ks) => ks.clone(),
+        }
+    }
+}
+
+impl Default for KRange {
+    fn default() -> Self {
+        KRange::Divisors { from_divisor: 10, to_divisor: 5 }
+    }
+}
+
+#[derive(Clone, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum DistributionSpec {
+    Uniform,
+    Harmonic,
+    Biharmonic,
+    Geometric { p: f64 },
+    /// Uniform over a working set of `size` pages that drifts by one page
+    /// with probability `drift` per request.
+    #[serde(rename = "working-set")]
+    WorkingSet { size: usize, drift: f64 },
+    /// LRU stack model with stack distances drawn from `depth`.
+    #[serde(rename = "stack-depth")]
+    StackDepth { depth: Box<DistributionSpec> },
+    /// Sequential scans of `length` requests every `period`, over `background`.
+    Scan { length: usize, period: usize, background: Box<DistributionSpec> },
+}
+
+impl DistributionSpec {
+    pub fn build(&self, n: usize, generator: SimRng) -> Distribution {
+        match self {
+            DistributionSpec::Uniform => Distribution::Uni(UniDistribution::new(n, generator)),
+            DistributionSpec::Harmonic => Distribution::Har(ArrDistribution::harmonic(n, generator)),
+            DistributionSpec::Biharmonic => Distribution::Bih(ArrDistribution::biharmonic(n, generator)),
+            DistributionSpec::Geometric { p } => Distribution::Geo(GeoDistribution::new(*p, n, generator)),
+            DistributionSpec::WorkingSet { size, drift } => {
+                Distribution::Ws(WorkingSetDistribution::new(*size, *drift, n, generator))
+            },
+            DistributionSpec::StackDepth { depth } => {
+                Distribution::Stack(StackDepthDistribution::new(n, depth.build(n, generator)))
+            },
+            DistributionSpec::Scan { length, period, background } => {
+                Distribution::Scan(ScanDistribution::new(*length, *period, n, background.build(n, generator)))
+            },
+        }
+    }
+
+    /// Checks the parameters for a universe of n pages.
+    fn validate(&self, n: usize) -> Result<(), String> {
+        match self {
+            DistributionSpec::Geometric { p } if !(0.0..=1.0).contains(p) => {
+                Err(format!("geometric p = {} is not in [0, 1]", p))
+            },
+            DistributionSpec::WorkingSet { size, .. } if *size == 0 || *size > n => {
+                Err(format!("working set size {} is not in 1..={}", size, n))
+            },
+            DistributionSpec::WorkingSet { drift, .. } if !(0.0..=1.0).contains(drift) => {
+                Err(format!("working set drift {} is not in [0, 1]", drift))
+            },
+            DistributionSpec::StackDepth { depth } => depth.validate(n),
+            DistributionSpec::Scan { length, period, .. } if *period == 0 || length > period => {
+                Err(format!("scan of length {} every {} requests needs 0 < period and length <= period", length, period))
+            },
+            DistributionSpec::Scan { background, .. } => background.validate(n),
+            _ => Ok(()),
+        }
+    }
+}
+
+fn invalid(msg: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+/// Reads a spec, as JSON if the extension is `.json` and TOML otherwise.
+fn read_spec<T: DeserializeOwned>(path: &Path) -> io::Result<T> {
+    let text = fs::read_to_string(path)?;
+    match path.extension().and_then(|ext| ext.to_str()) {
+        Some("json") => serde_json::from_str(&text).map_err(|e| invalid(e.to_string())),
+        _ => toml::from_str(&text).map_err(|e| invalid(e.to_string())),
+    }
+}
+
+fn default_output_dir() -> String {
+    "data".to_owned()
+}
+
+fn default_write_back_cost() -> f64 {
+    1.0
+}
+
+/// Description of a lab2 sweep, read from a TOML or JSON file.
+#[derive(Clone, Deserialize)]
+pub struct ExperimentSpec {
+    pub ns              : Vec<usize>,
+    #[serde(default)]
+    pub ks              : KRange,
+    pub distributions   : Vec<DistributionSpec>,
+    pub policies        : Vec<CacheType>,
+    pub requests        : usize,
+    pub repetitions     : usize,
+    pub seed            : Option<u64>,
+    #[serde(default = "default_output_dir")]
+    pub output_dir      : String,
+    /// Probability that a request is a write.
+    #[serde(default)]
+    pub write_fraction  : f64,
+    #[serde(default = "default_write_back_cost")]
+    pub write_back_cost : f64,
+}
+
+impl ExperimentSpec {
+    /// The sweep the lab was originally run with.
+    pub fn lab() -> Self {
+        ExperimentSpec {
+            ns              : vec![20, 30, 40, 50, 60, 70, 80, 90, 100],
+            ks              : KRange::default(),
+            distributions   : vec![
+                DistributionSpec::Uniform,
+                DistributionSpec::Harmonic,
+                DistributionSpec::Biharmonic,
+                DistributionSpec::Geometric { p: 0.5 },
+            ],
+            policies        : vec![
+                CacheType::Fifo,
+                CacheType::Fwf,
+                CacheType::Lru,
+                CacheType::Lfu,
+                CacheType::Rand,
+                CacheType::Rma,
+            ],
+            requests        : 100_000,
+            repetitions     : 10,
+            seed            : None,
+            output_dir      : default_output_dir(),
+            write_fraction  : 0.0,
+            write_back_cost : default_write_back_cost(),
+        }
+    }
+
+    /// Reads a spec and checks that every run it describes can be made.
+    pub fn read(path: &Path) -> io::Result<Self> {
+        let spec: Self = read_spec(path)?;
+        spec.validate().map_err(invalid)?;
+        Ok(spec)
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if self.requests == 0 {
+            return Err("requests must be positive".to_owned());
+        }
+        // paired differences need a sample variance
+        if self.repetitions < 2 {
+            return Err(format!("{} repetitions, at least 2 are needed", self.repetitions));
+        }
+        if !(0.0..=1.0).contains(&self.write_fraction) {
+            return Err(format!("write fraction {} is not in [0, 1]", self.write_fraction));
+        }
+        if let KRange::Divisors { from_divisor: 0, .. } | KRange::Divisors { to_divisor: 0, .. } = self.ks {
+            return Err("k divisors must be positive".to_owned());
+        }
+        for &n in self.ns.iter() {
+            if n == 0 {
+                return Err("n must be positive".to_owned());
+            }
+            if self.ks.ks(n).contains(&0) {
+                return Err(format!("cache size 0 for n = {}", n));
+            }
+            for dist in self.distributions.iter() {
+                dist.validate(n)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Distributions over 1..=n, each drawing from its own stream of the seed.
+    pub fn distributions(&self, n: usize, seed: u64) -> Vec<Distribution> {
+        self.distributions
+            .iter()
+            .enumerate()
+            .map(|(d, dist)| dist.build(n, stream(seed, &[n as u64, d as u64])))
+            .collect()
+    }
+}
+
+fn default_weight() -> f64 {
+    1.0
+}
+
+fn default_epoch() -> usize {
+    10_000
+}
+
+/// One request stream of a multi-tenant run.
+#[derive(Clone, Deserialize)]
+pub struct TenantConfig {
+    pub n            : usize,
+    pub distribution : DistributionSpec,
+    /// Relative request rate.
+    #[serde(default = "default_weight")]
+    pub weight       : f64,
+    /// Static partition; tenants without one split what is left equally.
+    pub share        : Option<usize>,
+}
+
+/// Description of a multi-tenant run, read from a TOML or JSON file.
+#[derive(Clone, Deserialize)]
+pub struct TenantSpec {
+    pub tenants     : Vec<TenantConfig>,
+    pub k           : usize,
+    pub policy      : CacheType,
+    pub requests    : usize,
+    pub repetitions : usize,
+    /// Requests between repartitions in utility mode.
+    #[serde(default = "default_epoch")]
+    pub epoch       : usize,
+    pub seed        : Option<u64>,
+    #[serde(default = "default_output_dir")]
+    pub output_dir  : String,
+}
+
+impl TenantSpec {
+    /// Reads a spec and checks that every tenant gets part of the cache.
+    pub fn read(path: &Path) -> io::Result<Self> {
+        let spec: Self = read_spec(path)?;
+        spec.validate().map_err(invalid)?;
+        Ok(spec)
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if self.tenants.is_empty() {
+            return Err("no tenants".to_owned());
+        }
+        if self.requests == 0 || self.repetitions == 0 || self.epoch == 0 {
+            return Err("requests, repetitions and epoch must be positive".to_owned());
+        }
+        for (t, tenant) in self.tenants.iter().enumerate() {
+            if tenant.n == 0 {
+                return Err(format!("tenant {} has no pages", t));
+            }
+            if !(tenant.weight > 0.0 && tenant.weight.is_finite()) {
+                return Err(format!("tenant {} has weight {}, it must be positive", t, tenant.weight));
+            }
+            tenant.distribution.validate(tenant.n)?;
+        }
+        let fixed = self.tenants.iter().filter_map(|tenant| tenant.share).sum::<usize>();
+        if fixed > self.k {
+            return Err(format!("fixed shares take {} pages of a cache of {}", fixed, self.k));
+        }
+        if let Some(t) = self.partition().iter().position(|&share| share == 0) {
+            return Err(format!("tenant {} gets no share of a cache of {}", t, self.k));
+        }
+        Ok(())
+    }
+
+    pub fn ns(&self) -> Vec<usize> {
+        self.tenants.iter().map(|tenant| tenant.n).collect()
+    }
+
+    pub fn weights(&self) -> Vec<f64> {
+        self.tenants.iter().map(|tenant| tenant.weight).collect()
+    }
+
+    /// Static partition of every tenant.
+    pub fn partition(&self) -> Vec<usize> {
+        let fixed = self.tenants.iter().filter_map(|tenant| tenant.share).sum::<usize>();
+        let mut rest = self.tenants.iter().filter(|tenant| tenant.share.is_none()).count();
+        let mut left = self.k.saturating_sub(fixed);
+        let mut partition = Vec::new();
+        for tenant in self.tenants.iter() {
+            let share = match tenant.share {
+                Some(share) => share,
+                None => {
+                    let share = left / rest;
+                    left -= share;
+                    rest -= 1;
+                    share
+                },
+            };
+            partition.push(share);
+        }
+        partition
+    }
+
+    /// Each tenant's distribution over its own pages, on its own stream of the seed.
+    pub fn distributions(&self, seed: u64) -> Vec<Distribution> {
+        self.tenants
+            .iter()
+            .enumerate()
+            .map(|(t, tenant)| tenant.distribution.build(tenant.n, stream(seed, &[t as u64])))
+            .collect()
+    }
+}
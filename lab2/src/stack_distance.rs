@@ -0,0 +1,222 @@
+use crate::cache::*;
+use crate::rng::*;
+use rand::SeedableRng;
+use std::collections::HashMap;
+
+/// Fenwick tree over access times that can grow one slot at a time.
+struct Fenwick {
+    tree: Vec<i64>,
+}
+
+impl Fenwick {
+    fn new() -> Self {
+        Fenwick { tree: vec![0] }
+    }
+
+    fn lowbit(i: usize) -> usize {
+        i & i.wrapping_neg()
+    }
+
+    /// Sum of slots 1..=i.
+    fn prefix(&self, mut i: usize) -> i64 {
+        let mut sum = 0;
+        while i > 0 {
+            sum += self.tree[i];
+            i -= Self::lowbit(i);
+        }
+        sum
+    }
+
+    /// Appends a new slot holding `val` and returns its index.
+    fn push(&mut self, val: i64) -> usize {
+        let i = self.tree.len();
+        let node = self.prefix(i - 1) - self.prefix(i - Self::lowbit(i)) + val;
+        self.tree.push(node);
+        i
+    }
+
+    fn add(&mut self, mut i: usize, val: i64) {
+        while i < self.tree.len() {
+            self.tree[i] += val;
+            i += Self::lowbit(i);
+        }
+    }
+}
+
+const SHARDS_MODULUS: u64 = 1 << 24;
+
+/// LRU miss counts for every cache size, as produced by `StackDistance`.
+pub struct MissRatioCurve {
+    requests    : u64,
+    cold_misses : u64,
+    histogram   : Vec<u64>,
+}
+
+impl MissRatioCurve {
+    /// Number of LRU faults for a cache of size k.
+    pub fn misses(&self, k: usize) -> u64 {
+        let capacity_misses: u64 = self.histogram.iter().skip(k + 1).sum();
+        self.cold_misses + capacity_misses
+    }
+
+    /// Fraction of requests that fault in an LRU cache of size k.
+    pub fn miss_ratio(&self, k: usize) -> f64 {
+        if self.requests == 0 {
+            return 0.0;
+        }
+        self.misses(k) as f64 / self.requests as f64
+    }
+}
+
+/// Mattson stack-distance analyzer: a single pass over a request sequence
+/// gives the LRU miss-ratio curve for all cache sizes at once.
+///
+/// With a sampling rate below 1 only pages whose hash falls under the
+/// threshold are tracked and their distances are scaled up (SHARDS).
+pub struct StackDistance {
+    time        : HashMap<usize, usize>,
+    marks       : Fenwick,
+    histogram   : Vec<u64>,
+    cold_misses : u64,
+    requests    : u64,
+    threshold   : u64,
+    rate        : f64,
+}
+
+impl StackDistance {
+    pub fn new() -> Self {
+        Self::with_sampling(1.0)
+    }
+
+    pub fn with_sampling(rate: f64) -> Self {
+        assert!(rate > 0.0 && rate <= 1.0, "sampling rate must be in (0, 1]");
+        StackDistance {
+            time        : HashMap::new(),
+            marks       : Fenwick::new(),
+            histogram   : vec![0; 1],
+            cold_misses : 0,
+            requests    : 0,
+            threshold   : (rate * SHARDS_MODULUS as f64) as u64,
+            rate,
+        }
+    }
+
+    /// Records a request and returns its (scaled) stack distance,
+    /// or None for a first reference or a page skipped by sampling.
+    pub fn access(&mut self, page: usize) -> Option<usize> {
+        // mixed so that sampling does not follow page numbering
+        if self.rate < 1.0 && splitmix(page as u64) % SHARDS_MODULUS >= self.threshold {
+            return None;
+        }
+        self.requests += 1;
+
+        let now = self.marks.push(1);
+        match self.time.insert(page, now) {
+            Some(last) => {
+                // distinct pages touched since `last`, including the page itself
+                let depth = (self.marks.prefix(now - 1) - self.marks.prefix(last - 1)) as usize;
+                self.marks.add(last, -1);
+
+                let dist = (depth as f64 / self.rate).round() as usize;
+                if dist >= self.histogram.len() {
+                    self.histogram.resize(dist + 1, 0);
+                }
+                self.histogram[dist] += 1;
+                Some(dist)
+            }
+            None => {
+                self.cold_misses += 1;
+                None
+            }
+        }
+    }
+
+    pub fn process(&mut self, seq: &[usize]) {
+        for page in seq {
+            self.access(*page);
+        }
+    }
+
+    pub fn curve(&self) -> MissRatioCurve {
+        MissRatioCurve {
+            requests    : self.requests,
+            cold_misses : self.cold_misses,
+            histogram   : self.histogram.clone(),
+        }
+    }
+}
+
+impl Default for StackDistance {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Replays `seq` through an LRU `Cache` for each k and checks the fault
+/// counts against an exact miss-ratio curve. Returns the mismatching ks.
+pub fn validate_lru(curve: &MissRatioCurve, seq: &[usize], n: usize, ks: &[usize]) -> Vec<usize> {
+    // LRU never draws from the generator
+    let mut rand = SimRng::seed_from_u64(0);
+    ks.iter()
+        .copied()
+        .filter(|&k| {
+            let mut cache = Cache::new(n, k, CacheType::Lru);
+            let faults: usize = seq.iter().map(|&page| cache.get_page(page, &mut rand)).sum();
+            faults as u64 != curve.misses(k)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::Rng;
+
+    /// Requests mostly to a hot set, so that every cache size gets hits.
+    fn sequence(rand: &mut SimRng, n: usize, len: usize) -> Vec<usize> {
+        let hot = n.div_ceil(4);
+        (0..len)
+            .map(|_| match rand.gen_bool(0.7) {
+                true => rand.gen_range(1..=hot),
+                false => rand.gen_range(1..=n),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn exact_curve_matches_lru_faults() {
+        let mut rand = SimRng::seed_from_u64(26);
+        for _ in 0..50 {
+            let n = rand.gen_range(1..=20);
+            let len = rand.gen_range(0..300);
+            let seq = sequence(&mut rand, n, len);
+            let mut analyzer = StackDistance::new();
+            analyzer.process(&seq);
+            let curve = analyzer.curve();
+            for k in 1..=n + 1 {
+                let mut cache = Cache::new(n, k, CacheType::Lru);
+                let faults: usize = seq.iter().map(|&page| cache.get_page(page, &mut rand)).sum();
+                assert_eq!(curve.misses(k), faults as u64, "k = {} on {:?}", k, seq);
+            }
+            assert!(validate_lru(&curve, &seq, n, &[1, n]).is_empty());
+        }
+    }
+
+    #[test]
+    fn sampled_curve_stays_close_to_exact() {
+        let mut rand = SimRng::seed_from_u64(27);
+        let n = 20_000;
+        let seq = sequence(&mut rand, n, 200_000);
+        let mut exact = StackDistance::new();
+        exact.process(&seq);
+        let mut sampled = StackDistance::with_sampling(0.1);
+        sampled.process(&seq);
+        let (exact, sampled) = (exact.curve(), sampled.curve());
+        let share = sampled.requests as f64 / exact.requests as f64;
+        assert!((0.05..0.15).contains(&share), "sampled {} of the requests", share);
+        for k in (1000..=n).step_by(1000) {
+            let error = (exact.miss_ratio(k) - sampled.miss_ratio(k)).abs();
+            assert!(error < 0.03, "miss ratio off by {} at k = {}", error, k);
+        }
+    }
+}
@@ -0,0 +1,113 @@
+use crate::marking::*;
+use crate::stack_distance::*;
+
+/// Counters collected by a `Cache` with statistics enabled.
+///
+/// Misses are split the 3C way: compulsory on a first reference, conflict
+/// when a fully associative LRU cache of the same size would have hit and
+/// capacity otherwise. Misses on pages whose TTL ran out while resident
+/// are counted apart as expired, and pages dropped that way are not
+/// counted as evictions.
+#[derive(Clone, Default)]
+pub struct CacheStats {
+    pub hits              : usize,
+    pub misses            : usize,
+    pub compulsory_misses : usize,
+    pub capacity_misses   : usize,
+    pub conflict_misses   : usize,
+    pub expired_misses    : usize,
+    pub evictions         : usize,
+    pub write_backs       : usize,
+    pub page_hits         : Vec<usize>,
+    pub phases            : usize,
+    pub opt_lower_bound   : usize,
+}
+
+impl CacheStats {
+    pub fn hit_ratio(&self) -> f64 {
+        let requests = self.hits + self.misses;
+        if requests == 0 {
+            return 0.0;
+        }
+        self.hits as f64 / requests as f64
+    }
+}
+
+#[derive(Clone, Copy)]
+pub enum CacheEvent {
+    Insert { time: usize, page: usize },
+    Prefetch { time: usize, page: usize },
+    Evict { time: usize, victim: usize, page: usize },
+    Expire { time: usize, page: usize },
+}
+
+impl CacheEvent {
+    pub fn to_line(self) -> String {
+        match self {
+            CacheEvent::Insert { time, page } => format!("{};insert;{}\n", time, page),
+            CacheEvent::Prefetch { time, page } => format!("{};prefetch;{}\n", time, page),
+            CacheEvent::Evict { time, victim, page } => format!("{};evict;{};{}\n", time, victim, page),
+            CacheEvent::Expire { time, page } => format!("{};expire;{}\n", time, page),
+        }
+    }
+}
+
+/// Bookkeeping behind `CacheStats`, including the shadow LRU stack
+/// used to tell capacity misses from conflict misses.
+pub struct StatsTracker {
+    k      : usize,
+    stats  : CacheStats,
+    shadow : StackDistance,
+    phases : PhaseTracker,
+}
+
+impl StatsTracker {
+    pub fn new(n: usize, k: usize) -> Self {
+        StatsTracker {
+            k,
+            stats  : CacheStats {
+                page_hits : vec![0; n + 1],
+                ..CacheStats::default()
+            },
+            shadow : StackDistance::new(),
+            phases : PhaseTracker::new(k),
+        }
+    }
+
+    pub fn record(&mut self, page: usize, hit: bool, expired: bool) {
+        let dist = self.shadow.access(page);
+        self.phases.request(page);
+        self.stats.phases = self.phases.phases();
+        self.stats.opt_lower_bound = self.phases.opt_lower_bound();
+        if hit {
+            self.stats.hits += 1;
+            self.stats.page_hits[page] += 1;
+            return;
+        }
+
+        self.stats.misses += 1;
+        match dist {
+            _ if expired => self.stats.expired_misses += 1,
+            None => self.stats.compulsory_misses += 1,
+            Some(d) if d <= self.k => self.stats.conflict_misses += 1,
+            Some(_) => self.stats.capacity_misses += 1,
+        }
+    }
+
+    pub fn record_eviction(&mut self, dirty: bool) {
+        self.stats.evictions += 1;
+        self.record_write_back(dirty);
+    }
+
+    /// A page leaving the cache other than by eviction, i.e. expiring,
+    /// is still written back if dirty.
+    pub fn record_write_back(&mut self, dirty: bool) {
+        if dirty {
+            self.stats.write_backs += 1;
+        }
+    }
+
+    pub fn stats(&self) -> &CacheStats {
+        &self.stats
+    }
+}
@@ -0,0 +1,156 @@
+use crate::cache::*;
+use crate::stack_distance::*;
+use rand::RngCore;
+use serde::Deserialize;
+
+/// How tenants share the k pages of a `TenantCache`.
+#[derive(Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PartitionMode {
+    /// One unpartitioned cache over the pages of every tenant.
+    Shared,
+    /// A fixed partition per tenant.
+    Static,
+    /// Partitions resized every epoch from each tenant's utility curve.
+    Utility,
+}
+
+impl PartitionMode {
+    pub const ALL: [PartitionMode; 3] = [
+        PartitionMode::Shared,
+        PartitionMode::Static,
+        PartitionMode::Utility,
+    ];
+
+    pub fn name(&self) -> &str {
+        match self {
+            PartitionMode::Shared  => "shared",
+            PartitionMode::Static  => "static",
+            PartitionMode::Utility => "utility",
+        }
+    }
+}
+
+/// Splits k pages among tenants with the UCP lookahead rule: every tenant
+/// gets one page, then the rest go, a block at a time, to the tenant whose
+/// curve gains the most hits per page from the block.
+pub fn lookahead_partition(curves: &[MissRatioCurve], k: usize) -> Vec<usize> {
+    assert!(k >= curves.len(), "every tenant needs at least one page");
+    let mut partition = vec![1; curves.len()];
+    let mut balance = k - curves.len();
+    while balance > 0 {
+        let mut best = (0, 1, -1.0);
+        for (t, curve) in curves.iter().enumerate() {
+            let misses = curve.misses(partition[t]);
+            for extra in 1..=balance {
+                let gain = (misses - curve.misses(partition[t] + extra)) as f64 / extra as f64;
+                if gain > best.2 {
+                    best = (t, extra, gain);
+                }
+            }
+        }
+        partition[best.0] += best.1;
+        balance -= best.1;
+    }
+    partition
+}
+
+/// Cache of size k serving several tenants, tenant t owning pages 1..=ns[t].
+pub struct TenantCache {
+    mode     : PartitionMode,
+    k        : usize,
+    epoch    : usize,
+    offsets  : Vec<usize>,         // first global page of each tenant, minus one
+    caches   : Vec<Cache>,         // a single one in shared mode
+    monitors : Vec<StackDistance>, // per tenant, over the current epoch
+    time     : usize,
+    requests : Vec<usize>,
+    faults   : Vec<usize>,
+}
+
+impl TenantCache {
+    /// `partition` gives the static partitions, which also serve as the
+    /// first epoch's in utility mode; it must sum to at most k.
+    pub fn new(mode: PartitionMode, ns: &[usize], k: usize, partition: &[usize], epoch: usize, cache_type: CacheType) -> Self {
+        assert_eq!(ns.len(), partition.len(), "one partition per tenant");
+        assert!(partition.iter().all(|&share| share > 0), "every tenant needs at least one page");
+        assert!(partition.iter().sum::<usize>() <= k, "partitions exceed the cache");
+        let offsets = ns
+            .iter()
+            .scan(0, |offset, &n| {
+                *offset += n;
+                Some(*offset - n)
+            })
+            .collect::<Vec<usize>>();
+        let caches = match mode {
+            PartitionMode::Shared => vec![Cache::new(ns.iter().sum(), k, cache_type)],
+            _ => ns
+                .iter()
+                .zip(partition.iter())
+                .map(|(&n, &share)| Cache::new(n, share, cache_type))
+                .collect(),
+        };
+        TenantCache {
+            mode,
+            k,
+            epoch,
+            offsets,
+            caches,
+            monitors : ns.iter().map(|_| StackDistance::new()).collect(),
+            time     : 0,
+            requests : vec![0; ns.len()],
+            faults   : vec![0; ns.len()],
+        }
+    }
+
+    /// Current size of each tenant's partition, or of the one shared cache.
+    pub fn partition(&self) -> Vec<usize> {
+        self.caches.iter().map(|cache| cache.capacity()).collect()
+    }
+
+    pub fn requests(&self) -> &[usize] {
+        &self.requests
+    }
+
+    pub fn faults(&self) -> &[usize] {
+        &self.faults
+    }
+
+    /// Requests `page` of `tenant`. Returns 1 on a fault, 0 otherwise.
+    pub fn get_page<R: RngCore>(&mut self, tenant: usize, page: usize, rand: &mut R) -> usize {
+        self.time += 1;
+        let fault = match self.mode {
+            PartitionMode::Shared => self.caches[0].get_page(self.offsets[tenant] + page, rand),
+            _ => self.caches[tenant].get_page(page, rand),
+        };
+        self.requests[tenant] += 1;
+        self.faults[tenant] += fault;
+
+        if let PartitionMode::Utility = self.mode {
+            self.monitors[tenant].access(page);
+            if self.time.is_multiple_of(self.epoch) {
+                self.repartition(rand);
+            }
+        }
+        fault
+    }
+
+    fn repartition<R: RngCore>(&mut self, rand: &mut R) {
+        let curves = self.monitors.iter().map(|monitor| monitor.curve()).collect::<Vec<MissRatioCurve>>();
+        let partition = lookahead_partition(&curves, self.k);
+        for (cache, share) in self.caches.iter_mut().zip(partition) {
+            cache.resize(share, rand);
+        }
+        for monitor in self.monitors.iter_mut() {
+            *monitor = StackDistance::new();
+        }
+    }
+}
+
+/// Jain's fairness index: 1 when all values are equal, down to 1/len
+/// when one value dominates.
+pub fn jain_index(values: &[f64]) -> f64 {
+    let sum = values.iter().sum::<f64>();
+    let squares = values.iter().map(|v| v * v).sum::<f64>();
+    sum * sum / (values.len() as f64 * squares)
+}
@@ -0,0 +1,193 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, prelude::*, BufReader};
+use std::path::Path;
+
+/// Magic bytes opening a binary trace, followed by LEB128-encoded keys.
+const BIN_MAGIC: &[u8; 4] = b"TRC1";
+
+#[derive(Clone, Copy)]
+pub enum TraceFormat {
+    Text,   // one key per line
+    Csv,    // timestamp,key,size[,op[,ttl]]
+    Binary, // BIN_MAGIC + varint keys
+}
+
+impl TraceFormat {
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("csv") => TraceFormat::Csv,
+            Some("bin") => TraceFormat::Binary,
+            _ => TraceFormat::Text,
+        }
+    }
+}
+
+/// Maps raw trace keys to page ids usable by `Cache` (1..=n).
+pub enum PageMapper {
+    /// Numbers keys in order of first appearance.
+    Dense(HashMap<String, usize>),
+    /// Hashes keys into a fixed universe of n pages.
+    Hashed(usize),
+}
+
+impl PageMapper {
+    pub fn dense() -> Self {
+        PageMapper::Dense(HashMap::new())
+    }
+
+    pub fn hashed(n: usize) -> Self {
+        assert!(n > 0, "hashed page universe must not be empty");
+        PageMapper::Hashed(n)
+    }
+
+    pub fn map(&mut self, key: &str) -> usize {
+        match self {
+            PageMapper::Dense(ids) => {
+                let next = ids.len() + 1;
+                *ids.entry(key.to_owned()).or_insert(next)
+            },
+            PageMapper::Hashed(n) => (fnv1a(key.as_bytes()) % *n as u64) as usize + 1,
+        }
+    }
+
+    /// Size of the page universe seen so far.
+    pub fn universe(&self) -> usize {
+        match self {
+            PageMapper::Dense(ids) => ids.len(),
+            PageMapper::Hashed(n) => *n,
+        }
+    }
+}
+
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &b| {
+        (hash ^ b as u64).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+fn invalid(msg: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+/// Request sequence read from an access log. Only CSV traces carry
+/// writes, timestamps and TTLs; other formats are all reads, timestamped
+/// by position and without a TTL.
+pub struct Trace {
+    pub pages: Vec<usize>,
+    pub writes: Vec<bool>,
+    pub times: Vec<u64>,
+    pub ttls: Vec<Option<u64>>,
+    pub n: usize,
+}
+
+impl Trace {
+    pub fn read(path: &Path, format: TraceFormat, mapper: &mut PageMapper) -> io::Result<Self> {
+        let reader = BufReader::new(File::open(path)?);
+        let mut trace = match format {
+            TraceFormat::Text   => untimed(read_text(reader, mapper)?),
+            TraceFormat::Csv    => read_csv(reader, mapper)?,
+            TraceFormat::Binary => untimed(read_binary(reader, mapper)?),
+        };
+        if trace.pages.is_empty() {
+            return Err(invalid(format!("{}: no requests", path.display())));
+        }
+        trace.n = mapper.universe();
+        Ok(trace)
+    }
+
+    /// Time between the first and the last request.
+    pub fn span(&self) -> u64 {
+        match (self.times.first(), self.times.last()) {
+            (Some(first), Some(last)) => last.saturating_sub(*first),
+            _ => 0,
+        }
+    }
+}
+
+fn untimed(pages: Vec<usize>) -> Trace {
+    Trace {
+        writes: vec![false; pages.len()],
+        times: (1..=pages.len() as u64).collect(),
+        ttls: vec![None; pages.len()],
+        pages,
+        n: 0,
+    }
+}
+
+fn read_text(reader: impl BufRead, mapper: &mut PageMapper) -> io::Result<Vec<usize>> {
+    let mut pages = Vec::new();
+    for line in reader.lines() {
+        let line = line?;
+        let key = line.trim();
+        if !key.is_empty() {
+            pages.push(mapper.map(key));
+        }
+    }
+    Ok(pages)
+}
+
+fn read_csv(reader: impl BufRead, mapper: &mut PageMapper) -> io::Result<Trace> {
+    let mut trace = untimed(Vec::new());
+    for (i, line) in reader.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let fields = line.split(',').map(str::trim).collect::<Vec<&str>>();
+        if !(3..=5).contains(&fields.len()) {
+            return Err(invalid(format!("line {}: expected timestamp,key,size[,op[,ttl]]", i + 1)));
+        }
+        let time = match fields[0].parse::<u64>() {
+            Ok(time) => time,
+            // header row
+            Err(_) if i == 0 => continue,
+            Err(_) => return Err(invalid(format!("line {}: bad timestamp {:?}", i + 1, fields[0]))),
+        };
+        let write = match fields.get(3).map(|op| op.to_ascii_lowercase()) {
+            None => false,
+            Some(op) if op.is_empty() => false,
+            Some(op) if op == "r" || op == "read" => false,
+            Some(op) if op == "w" || op == "write" => true,
+            Some(op) => return Err(invalid(format!("line {}: bad op {:?}", i + 1, op))),
+        };
+        let ttl = match fields.get(4) {
+            None | Some(&"") => None,
+            Some(ttl) => Some(ttl.parse::<u64>().map_err(|_| invalid(format!("line {}: bad ttl {:?}", i + 1, ttl)))?),
+        };
+        trace.pages.push(mapper.map(fields[1]));
+        trace.writes.push(write);
+        trace.times.push(time);
+        trace.ttls.push(ttl);
+    }
+    Ok(trace)
+}
+
+fn read_binary(mut reader: impl BufRead, mapper: &mut PageMapper) -> io::Result<Vec<usize>> {
+    let mut magic = [0; 4];
+    reader.read_exact(&mut magic)?;
+    if &magic != BIN_MAGIC {
+        return Err(invalid("missing binary trace header".to_owned()));
+    }
+
+    let mut pages = Vec::new();
+    let mut key: u64 = 0;
+    let mut shift = 0;
+    for byte in reader.bytes() {
+        let byte = byte?;
+        if shift >= 64 {
+            return Err(invalid("varint key overflows u64".to_owned()));
+        }
+        key |= ((byte & 0x7f) as u64) << shift;
+        shift += 7;
+        if byte & 0x80 == 0 {
+            pages.push(mapper.map(&key.to_string()));
+            key = 0;
+            shift = 0;
+        }
+    }
+    if shift != 0 {
+        return Err(invalid("truncated varint key".to_owned()));
+    }
+    Ok(pages)
+}
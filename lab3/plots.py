@@ -8,7 +8,8 @@ import numpy as np
 import matplotlib.pyplot as plt
 
 def plot_heatmap():
-    bin_packs = [ "BEST", "FIRST", "RANDOM",  "WORST", "NEXT" ]
+    bin_packs = [ "BEST", "FIRST", "RANDOM",  "WORST", "NEXT",
+                  "HARMONIC", "REFINED_HARMONIC", "MODIFIED_HARMONIC", "NEXT_K" ]
     distrs    = [ "uniform", "harmonic", "biharmonic", "geometric" ]
 
     data = [ [ read_float(bp, dist) for bp in bin_packs ] for dist in distrs ]
@@ -1,24 +1,30 @@
+use crate::bins::*;
+use crate::bounded::*;
+use crate::size::*;
 use fastrand::Rng;
-use std::vec;
 
 macro_rules! filtered_pairs {
     ($v:expr, $r: expr) => {
         $v
         .iter()
         .enumerate()
-        .filter(|(_, &x)| x + $r <= 1.0)
+        .filter(|(_, &x)| $r.fits(x))
     };
 }
 
-pub enum BinPacking {
+pub enum BinPacking<S: Size = Fixed> {
     Next,
     First,
     Best,
     Worst,
     Random(Rng),
+    Harmonic(Harmonic<S>),
+    RefinedHarmonic(Harmonic<S>),
+    ModifiedHarmonic(Harmonic<S>),
+    NextK(NextK),
 }
 
-impl BinPacking {
+impl<S: Size> BinPacking<S> {
     pub fn name(&self) -> &str {
         match self {
             BinPacking::Next => "NEXT",
@@ -26,73 +32,109 @@ impl BinPacking {
             BinPacking::Best => "BEST",
             BinPacking::Worst => "WORST",
             BinPacking::Random(_) => "RANDOM",
+            BinPacking::Harmonic(_) => "HARMONIC",
+            BinPacking::RefinedHarmonic(_) => "REFINED_HARMONIC",
+            BinPacking::ModifiedHarmonic(_) => "MODIFIED_HARMONIC",
+            BinPacking::NextK(_) => "NEXT_K",
         }
     }
 
-    pub fn find_idx(&mut self, r: f64, bins: &[f64]) -> Option<usize> {
+    /// Forgets the open bins of bounded-space algorithms, for a new sequence.
+    pub fn reset(&mut self) {
+        match self {
+            BinPacking::Harmonic(harmonic)
+            | BinPacking::RefinedHarmonic(harmonic)
+            | BinPacking::ModifiedHarmonic(harmonic) => harmonic.reset(),
+            BinPacking::NextK(next_k) => next_k.reset(),
+            _ => {},
+        }
+    }
+
+    /// Bin to put `r` in, or `None` to open a new one. `BinPack` starts
+    /// out with bin 0 empty and pushes a new bin, numbered `bins.len()`,
+    /// on `None`; algorithms that track bins by index rely on both.
+    pub fn find_idx(&mut self, r: S, bins: &Bins<S>) -> Option<usize> {
         match self {
             BinPacking::Next => {
                 let last = bins.len() - 1;
-                if bins[last] + r <= 1.0 {
+                if r.fits(bins.loads()[last]) {
                     Some(last)
                 } else {
                     None
                 }
             },
-            BinPacking::First => {
-                bins.iter().position(|&x| x + r <= 1.0)
-            },
-            BinPacking::Best => {
-                filtered_pairs!(bins, r)
-                .max_by(|(_, a), (_, b)| a.total_cmp(b))
-                .map(|(i, _)| i)
-            },
-            BinPacking::Worst => {
-                filtered_pairs!(bins, r)
-                .min_by(|(_, a), (_, b)| a.total_cmp(b))
-                .map(|(i, _)| i)
-            },
+            BinPacking::First => bins.first_fit(r),
+            BinPacking::Best => bins.best_fit(r),
+            BinPacking::Worst => bins.worst_fit(r),
             BinPacking::Random(rand) => {
-                let len = 
-                    filtered_pairs!(bins, r)
-                    .count();
-        
-                if len > 0 {
-                    filtered_pairs!(bins, r)
-                    .nth(rand.usize(0..len))
+                let fits = filtered_pairs!(bins.loads(), r)
                     .map(|(i, _)| i)
+                    .collect::<Vec<usize>>();
+
+                if !fits.is_empty() {
+                    Some(fits[rand.usize(0..fits.len())])
                 } else {
                     None
                 }
-            }
+            },
+            BinPacking::Harmonic(harmonic)
+            | BinPacking::RefinedHarmonic(harmonic)
+            | BinPacking::ModifiedHarmonic(harmonic) => harmonic.find_idx(r, bins.loads()),
+            BinPacking::NextK(next_k) => next_k.find_idx(r, bins.loads()),
         }
     }
 }
 
-pub struct BinPack<'a> {
-    bins : Vec<f64>,
-    bin_pack : &'a mut BinPacking
+pub struct BinPack<'a, S: Size = Fixed> {
+    bins : Bins<S>,
+    record : bool,
+    items : Vec<S>,
+    assignment : Vec<usize>, // bin of each item
+    bin_pack : &'a mut BinPacking<S>
 }
 
-impl<'a> BinPack<'a> {
-    pub fn new(bin_pack : &'a mut BinPacking) -> Self {
-        let bins = vec![ 0.0 ];
+impl<'a, S: Size> BinPack<'a, S> {
+    pub fn new(bin_pack : &'a mut BinPacking<S>) -> Self {
+        bin_pack.reset();
+        let bins = Bins::new();
         BinPack {
             bins,
+            record: false,
+            items: Vec::new(),
+            assignment: Vec::new(),
             bin_pack
         }
     }
 
-    pub fn add_val(&mut self, r: f64) {
+    /// Like `new`, but also keeps every item and the bin it went to, for
+    /// `items`, `assignment` and `contents`.
+    pub fn recording(bin_pack : &'a mut BinPacking<S>) -> Self {
+        BinPack {
+            record: true,
+            ..BinPack::new(bin_pack)
+        }
+    }
+
+    pub fn add_val(&mut self, r: S) {
         let idx_opt = self.bin_pack.find_idx(r, &self.bins);
 
-        match idx_opt {
-            Some(idx) => self.bins[idx] += r,
-            None => self.bins.push(r),
+        let idx = match idx_opt {
+            Some(idx) => {
+                self.bins.add(idx, r);
+                idx
+            },
+            None => {
+                self.bins.push(r);
+                self.bins.len() - 1
+            },
+        };
+        if self.record {
+            self.items.push(r);
+            self.assignment.push(idx);
         }
     }
 
-    pub fn pack(&mut self, rs: &[f64]) {
+    pub fn pack(&mut self, rs: &[S]) {
         for r in rs {
             self.add_val(*r);
         }
@@ -101,8 +143,37 @@ impl<'a> BinPack<'a> {
     pub fn num_of_bins(&self) -> usize {
         self.bins.len()
     }
+
+    pub fn name(&self) -> &str {
+        self.bin_pack.name()
+    }
+
+    /// Items packed so far, in order. Empty unless made with `recording`.
+    pub fn items(&self) -> &[S] {
+        &self.items
+    }
+
+    /// Bin each item went to. Empty unless made with `recording`.
+    pub fn assignment(&self) -> &[usize] {
+        &self.assignment
+    }
+
+    /// Load of each bin.
+    pub fn levels(&self) -> &[S] {
+        self.bins.loads()
+    }
+
+    /// Items of each bin, in the order they came. All empty unless made with
+    /// `recording`.
+    pub fn contents(&self) -> Vec<Vec<usize>> {
+        let mut contents = vec![Vec::new(); self.bins.len()];
+        for (item, &bin) in self.assignment.iter().enumerate() {
+            contents[bin].push(item);
+        }
+        contents
+    }
 }
 
-pub fn optimal_packing(arr : &[f64]) -> f64 {
-    arr.iter().sum::<f64>().ceil()
-}
\ No newline at end of file
+pub fn optimal_packing<S: Size>(arr : &[S]) -> f64 {
+    arr.iter().copied().sum::<S>().bins_for() as f64
+}
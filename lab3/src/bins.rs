@@ -0,0 +1,187 @@
+use crate::size::*;
+use std::collections::BTreeSet;
+
+/// Minimum load over ranges of bins, for First Fit: the leftmost bin an
+/// item fits in is found by descending towards subtrees whose minimum does.
+struct MinTree<S: Size> {
+    size  : usize,  // leaves, a power of two
+    nodes : Vec<S>, // 1-based heap order, unused leaves full
+}
+
+impl<S: Size> MinTree<S> {
+    fn new() -> Self {
+        MinTree {
+            size  : 1,
+            nodes : vec![S::FULL; 2],
+        }
+    }
+
+    fn set(&mut self, i: usize, load: S) {
+        if i >= self.size {
+            self.grow();
+        }
+        let mut node = self.size + i;
+        self.nodes[node] = load;
+        while node > 1 {
+            node /= 2;
+            self.nodes[node] = min(self.nodes[2 * node], self.nodes[2 * node + 1]);
+        }
+    }
+
+    /// Doubles the leaves, keeping the old ones in the left half.
+    fn grow(&mut self) {
+        let leaves = self.nodes.split_off(self.size);
+        self.size *= 2;
+        self.nodes = vec![S::FULL; 2 * self.size];
+        self.nodes[self.size..self.size + leaves.len()].copy_from_slice(&leaves);
+        for node in (1..self.size).rev() {
+            self.nodes[node] = min(self.nodes[2 * node], self.nodes[2 * node + 1]);
+        }
+    }
+
+    fn first_fit(&self, r: S) -> Option<usize> {
+        if !r.fits(self.nodes[1]) {
+            return None;
+        }
+        let mut node = 1;
+        while node < self.size {
+            node = if r.fits(self.nodes[2 * node]) { 2 * node } else { 2 * node + 1 };
+        }
+        Some(node - self.size)
+    }
+}
+
+fn min<S: Size>(a: S, b: S) -> S {
+    if b < a { b } else { a }
+}
+
+/// Bin loads, indexed for First, Best and Worst Fit in O(log m) per item.
+pub struct Bins<S: Size> {
+    loads : Vec<S>,
+    tree  : MinTree<S>,
+    set   : BTreeSet<(u64, usize)>, // load key and bin
+}
+
+impl<S: Size> Bins<S> {
+    /// A single empty bin, like `BinPack` starts with.
+    pub fn new() -> Self {
+        let mut bins = Bins {
+            loads : Vec::new(),
+            tree  : MinTree::new(),
+            set   : BTreeSet::new(),
+        };
+        bins.push(S::ZERO);
+        bins
+    }
+
+    pub fn loads(&self) -> &[S] {
+        &self.loads
+    }
+
+    pub fn len(&self) -> usize {
+        self.loads.len()
+    }
+
+    pub fn push(&mut self, load: S) {
+        self.loads.push(load);
+        self.tree.set(self.loads.len() - 1, load);
+        self.set.insert((load.key(), self.loads.len() - 1));
+    }
+
+    pub fn add(&mut self, i: usize, r: S) {
+        self.set.remove(&(self.loads[i].key(), i));
+        self.loads[i] += r;
+        self.tree.set(i, self.loads[i]);
+        self.set.insert((self.loads[i].key(), i));
+    }
+
+    /// Leftmost bin `r` fits in.
+    pub fn first_fit(&self, r: S) -> Option<usize> {
+        self.tree.first_fit(r)
+    }
+
+    /// Fullest bin `r` fits in.
+    pub fn best_fit(&self, r: S) -> Option<usize> {
+        // loads just under the room may still not fit
+        let limit = r.room().key();
+        self.set
+            .range(..=(limit, usize::MAX))
+            .rev()
+            .find(|&&(_, i)| r.fits(self.loads[i]))
+            .map(|&(_, i)| i)
+    }
+
+    /// Emptiest bin, if `r` fits in it.
+    pub fn worst_fit(&self, r: S) -> Option<usize> {
+        self.set
+            .first()
+            .map(|&(_, i)| i)
+            .filter(|&i| r.fits(self.loads[i]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fastrand::Rng;
+
+    /// The linear scans `Bins` replaces: leftmost fit, last fullest fit and
+    /// first emptiest fit.
+    fn scan<S: Size>(rule: usize, r: S, loads: &[S]) -> Option<usize> {
+        let fits = loads.iter().enumerate().filter(|(_, &x)| r.fits(x));
+        match rule {
+            0 => fits.map(|(i, _)| i).next(),
+            1 => fits.max_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap()).map(|(i, _)| i),
+            _ => fits.min_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap()).map(|(i, _)| i),
+        }
+    }
+
+    /// Runs of equal sizes, so that loads tie.
+    fn sequence<S: Size>(rand: &Rng, n: usize) -> Vec<S> {
+        let mut seq = Vec::with_capacity(n);
+        while seq.len() < n {
+            let r = S::from_fraction(rand.f64());
+            seq.extend(std::iter::repeat_n(r, rand.usize(1..=5)));
+        }
+        seq
+    }
+
+    fn same_assignments<S: Size>(seed: u64) {
+        let rand = Rng::with_seed(seed);
+        for _ in 0..200 {
+            let seq = sequence::<S>(&rand, rand.usize(1..2000));
+            for rule in 0..3 {
+                let mut bins = Bins::new();
+                let mut loads = vec![S::ZERO];
+                for &r in seq.iter() {
+                    let idx = match rule {
+                        0 => bins.first_fit(r),
+                        1 => bins.best_fit(r),
+                        _ => bins.worst_fit(r),
+                    };
+                    assert_eq!(idx, scan(rule, r, &loads));
+                    match idx {
+                        Some(i) => {
+                            bins.add(i, r);
+                            loads[i] += r;
+                        },
+                        None => {
+                            bins.push(r);
+                            loads.push(r);
+                        },
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn fixed_sizes_match_linear_scans() {
+        same_assignments::<Fixed>(47);
+    }
+
+    #[test]
+    fn float_sizes_match_linear_scans() {
+        same_assignments::<f64>(48);
+    }
+}
@@ -0,0 +1,233 @@
+use crate::size::*;
+use std::collections::VecDeque;
+
+/// What `find_idx` returns to open a bin, and the index the bin gets: the
+/// initial empty bin while it is unused, or else the one `BinPack` pushes
+/// on `None` (see `BinPacking::find_idx`).
+fn new_bin<S: Size>(bins: &[S]) -> (Option<usize>, usize) {
+    if bins.len() == 1 && bins[0] == S::ZERO {
+        (Some(0), 0)
+    } else {
+        (None, bins.len())
+    }
+}
+
+/// Harmonic-k (Lee and Lee): an item in (1/(j+1), 1/j] for j < k goes with
+/// j - 1 others of its class, and items of at most 1/k are packed Next Fit.
+///
+/// Refined Harmonic splits (1/2, 1] and (1/3, 1/2] at 1 - y and y: of
+/// the medium items in (1/3, y], every `period`-th is red and shares a bin
+/// with a large item in (1/2, 1 - y], the others are paired up as usual.
+///
+/// Modified Harmonic also fills gaps: an item of at most 1/3 that would
+/// open a bin of its class goes instead into the room left in a bin of
+/// two medium items, or of a large item still waiting for a red one, and
+/// its class keeps filling that bin while its items fit.
+pub struct Harmonic<S: Size> {
+    k       : usize,
+    y       : S,
+    period  : usize, // 0 for no red items
+    gap_fill: bool,
+    open    : Vec<Option<(usize, usize)>>, // bin and items in it, by class
+    mediums : usize,
+    larges  : Vec<usize>, // bins with a large item, waiting for a red one
+    reds    : Vec<usize>, // bins with a red item, waiting for a large one
+    pairs   : Vec<usize>, // bins with two medium items, their gap unused
+    gaps    : Vec<Option<usize>>, // gap being filled, by class
+}
+
+impl<S: Size> Harmonic<S> {
+    fn with(k: usize, y: f64, period: usize, gap_fill: bool) -> Self {
+        assert!(k >= 3, "Harmonic needs at least 3 classes");
+        Harmonic {
+            k,
+            y       : S::from_fraction(y),
+            period,
+            gap_fill,
+            open    : vec![None; k + 2],
+            mediums : 0,
+            larges  : Vec::new(),
+            reds    : Vec::new(),
+            pairs   : Vec::new(),
+            gaps    : vec![None; k + 1],
+        }
+    }
+
+    pub fn new(k: usize) -> Self {
+        Self::with(k, 0.5, 0, false)
+    }
+
+    /// Refined Harmonic (Lee and Lee): y = 37/96, one red item in 7, 20
+    /// classes.
+    pub fn refined() -> Self {
+        Self::with(20, 37.0 / 96.0, 7, false)
+    }
+
+    /// Modified Harmonic after Ramanan, Brown, Lee and Lee: y = 265/684,
+    /// one red item in 9, 20 classes, and small items in the gaps.
+    pub fn modified() -> Self {
+        Self::with(20, 265.0 / 684.0, 9, true)
+    }
+
+    pub fn reset(&mut self) {
+        self.open.iter_mut().for_each(|bin| *bin = None);
+        self.mediums = 0;
+        self.larges.clear();
+        self.reds.clear();
+        self.pairs.clear();
+        self.gaps.iter_mut().for_each(|bin| *bin = None);
+    }
+
+    /// Puts an item in the open bin of `class`, which takes `capacity`
+    /// items, opening a new one if there is none or it is full.
+    fn fill(&mut self, class: usize, capacity: usize, bins: &[S]) -> Option<usize> {
+        match self.open[class] {
+            Some((bin, items)) if items < capacity => {
+                self.open[class] = Some((bin, items + 1));
+                Some(bin)
+            },
+            _ => {
+                let (idx, bin) = new_bin(bins);
+                self.open[class] = Some((bin, 1));
+                idx
+            },
+        }
+    }
+
+    /// Puts an item in a bin of `waiting`, or opens one on `other`'s list.
+    fn pair(bins: &[S], waiting: &mut Vec<usize>, other: &mut Vec<usize>) -> Option<usize> {
+        match waiting.pop() {
+            Some(bin) => Some(bin),
+            None => {
+                let (idx, bin) = new_bin(bins);
+                other.push(bin);
+                idx
+            },
+        }
+    }
+
+    /// Bin with room for `r` left by larger items, if the class of `r` is
+    /// filling one or can start on one.
+    fn fill_gap(&mut self, class: usize, r: S, bins: &[S]) -> Option<usize> {
+        if let Some(bin) = self.gaps[class].filter(|&bin| r.fits(bins[bin])) {
+            return Some(bin);
+        }
+        let bin = match (self.pairs.last(), self.larges.last()) {
+            (Some(&bin), _) if r.fits(bins[bin]) => self.pairs.pop(),
+            (_, Some(&bin)) if r.fits(bins[bin]) => self.larges.pop(),
+            _ => None,
+        };
+        self.gaps[class] = bin;
+        bin
+    }
+
+    pub fn find_idx(&mut self, r: S, bins: &[S]) -> Option<usize> {
+        let refined = self.period > 0;
+        let (half, third) = (S::from_fraction(0.5), S::from_fraction(1.0 / 3.0));
+        if refined && r > half && r <= S::CAPACITY - self.y {
+            return Self::pair(bins, &mut self.reds, &mut self.larges);
+        }
+        if refined && r > third && r <= self.y {
+            self.mediums += 1;
+            if self.mediums.is_multiple_of(self.period) {
+                return Self::pair(bins, &mut self.larges, &mut self.reds);
+            }
+            let idx = self.fill(self.k + 1, 2, bins);
+            if let Some((bin, 2)) = self.open[self.k + 1].filter(|_| self.gap_fill) {
+                self.pairs.push(bin);
+            }
+            return idx;
+        }
+        let class = r.per_bin().clamp(1, self.k);
+        if self.gap_fill && class >= 3 {
+            if let Some(bin) = self.fill_gap(class, r, bins) {
+                return Some(bin);
+            }
+        }
+        if class < self.k {
+            return self.fill(class, class, bins);
+        }
+        // Next Fit for the smallest items
+        match self.open[class] {
+            Some((bin, items)) if r.fits(bins[bin]) => {
+                self.open[class] = Some((bin, items + 1));
+                Some(bin)
+            },
+            _ => {
+                let (idx, bin) = new_bin(bins);
+                self.open[class] = Some((bin, 1));
+                idx
+            },
+        }
+    }
+}
+
+/// Next-k-Fit: the k most recently opened bins stay open and take items
+/// First Fit; opening one more closes the oldest.
+pub struct NextK {
+    k    : usize,
+    open : VecDeque<usize>,
+}
+
+impl NextK {
+    pub fn new(k: usize) -> Self {
+        assert!(k >= 1, "Next-k-Fit needs an open bin");
+        NextK {
+            k,
+            open : VecDeque::from([0]),
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.open.clear();
+        self.open.push_back(0);
+    }
+
+    pub fn find_idx<S: Size>(&mut self, r: S, bins: &[S]) -> Option<usize> {
+        let fit = self.open.iter().copied().find(|&bin| r.fits(bins[bin]));
+        if fit.is_none() {
+            if self.open.len() == self.k {
+                self.open.pop_front();
+            }
+            self.open.push_back(bins.len());
+        }
+        fit
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bin_pack::*;
+    use fastrand::Rng;
+
+    fn bins(mut bin_pack: BinPacking<Fixed>, items: &[f64]) -> usize {
+        let mut packing = BinPack::new(&mut bin_pack);
+        let items = items.iter().map(|&x| Fixed::from_fraction(x)).collect::<Vec<Fixed>>();
+        packing.pack(&items);
+        packing.num_of_bins()
+    }
+
+    #[test]
+    fn small_items_fill_the_gaps() {
+        // four large bins waiting for red items take two items of 0.2 each
+        let items = [[0.55; 4].as_slice(), &[0.2; 8]].concat();
+        assert_eq!(bins(BinPacking::RefinedHarmonic(Harmonic::refined()), &items), 6);
+        assert_eq!(bins(BinPacking::ModifiedHarmonic(Harmonic::modified()), &items), 4);
+        // two mediums leave room for one
+        assert_eq!(bins(BinPacking::RefinedHarmonic(Harmonic::refined()), &[0.35, 0.35, 0.2]), 2);
+        assert_eq!(bins(BinPacking::ModifiedHarmonic(Harmonic::modified()), &[0.35, 0.35, 0.2]), 1);
+    }
+
+    #[test]
+    fn gaps_never_overflow() {
+        let rand = Rng::with_seed(49);
+        let mut bin_pack = BinPacking::ModifiedHarmonic(Harmonic::modified());
+        for _ in 0..200 {
+            let items = (0..rand.usize(1..2000)).map(|_| Fixed::from_fraction(rand.f64())).collect::<Vec<Fixed>>();
+            let mut packing = BinPack::new(&mut bin_pack);
+            packing.pack(&items);
+            assert!(packing.levels().iter().all(|&load| load <= Fixed::CAPACITY));
+        }
+    }
+}
@@ -1,3 +1,4 @@
+use crate::size::*;
 use fastrand::Rng;
 
 pub struct UniDistribution {
@@ -116,12 +117,12 @@ impl Distribution {
         }
     }
 
-    pub fn gen_seq(&mut self, n: usize) -> Vec<f64> {
+    pub fn gen_seq<S: Size>(&mut self, n: usize) -> Vec<S> {
         let mut seq = Vec::with_capacity(n);
         let rand = Rng::new();
 
         while seq.len() < n {
-            let r: f64 = rand.f64();
+            let r = S::from_fraction(rand.f64());
             let k = self.generate();
             for _ in 0..std::cmp::min(k, n - seq.len()) {
                 seq.push(r);
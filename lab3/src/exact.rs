@@ -0,0 +1,411 @@
+use std::collections::HashMap;
+
+/// Slack for comparing sums of item sizes, which pick up rounding errors:
+/// items fit in a bin if their sizes sum to at most `CAPACITY`, so every
+/// bound below is for bins of that size. It stays below a `Fixed` unit,
+/// whose sizes the f64 sums keep exact.
+const EPS: f64 = 1e-10;
+
+const CAPACITY: f64 = 1.0 + EPS;
+
+/// Search nodes after which `exact_packing` gives up on proving optimality.
+const NODE_LIMIT: usize = 20_000;
+
+/// Column generation rounds for the LP bound.
+const LP_ROUNDS: usize = 500;
+
+fn ceil(x: f64) -> usize {
+    (x - EPS).ceil().max(0.0) as usize
+}
+
+/// Martello–Toth L2 lower bound for items sorted in decreasing order.
+///
+/// For a threshold a <= C/2, items above C - a need a bin each, items in
+/// (C/2, C - a] too, and items in [a, C/2] can only use the space the
+/// latter leave free, so L(a) = |J1| + |J2| + ceil((s(J3) - free(J2)) / C).
+pub fn l2_bound(sorted: &[f64]) -> usize {
+    let sum = sorted.iter().sum::<f64>();
+    let mut best = ceil(sum / CAPACITY);
+    let thresholds = sorted.iter().filter(|&&s| s <= CAPACITY / 2.0);
+    for &a in thresholds {
+        let mut big = 0;
+        let mut free = 0.0;
+        let mut small = 0.0;
+        for &s in sorted.iter() {
+            if s > CAPACITY - a {
+                big += 1;
+            } else if s > CAPACITY / 2.0 {
+                big += 1;
+                free += CAPACITY - s;
+            } else if s >= a {
+                small += s;
+            }
+        }
+        best = best.max(big + ceil((small - free) / CAPACITY));
+    }
+    best
+}
+
+/// First Fit Decreasing on items sorted in decreasing order.
+fn first_fit_decreasing(sorted: &[f64]) -> usize {
+    let mut bins: Vec<f64> = Vec::new();
+    for &s in sorted {
+        match bins.iter_mut().find(|load| **load + s <= 1.0) {
+            Some(load) => *load += s,
+            None => bins.push(s),
+        }
+    }
+    bins.len()
+}
+
+/// Bounded knapsack over a bin of size 1: at most `counts[i]` items of size
+/// `sizes[i]`, each worth `values[i]`.
+struct Knapsack<'a> {
+    sizes  : &'a [f64],
+    counts : &'a [usize],
+    values : &'a [f64],
+    order  : Vec<usize>, // worth taking, by value per size
+    chosen : Vec<usize>,
+    best   : (f64, Vec<usize>),
+}
+
+impl<'a> Knapsack<'a> {
+    fn solve(sizes: &'a [f64], counts: &'a [usize], values: &'a [f64]) -> (f64, Vec<usize>) {
+        let mut order = (0..sizes.len())
+            .filter(|&i| values[i] > EPS && counts[i] > 0)
+            .collect::<Vec<usize>>();
+        order.sort_by(|&a, &b| (values[b] / sizes[b]).total_cmp(&(values[a] / sizes[a])));
+        let mut knapsack = Knapsack {
+            sizes,
+            counts,
+            values,
+            order,
+            chosen : vec![0; sizes.len()],
+            best   : (0.0, vec![0; sizes.len()]),
+        };
+        knapsack.branch(0, 1.0, 0.0);
+        knapsack.best
+    }
+
+    fn branch(&mut self, j: usize, space: f64, value: f64) {
+        if value > self.best.0 {
+            self.best = (value, self.chosen.clone());
+        }
+        // the fractional relaxation over the rest bounds what is left to gain
+        let mut bound = value;
+        let mut room = space;
+        for &i in self.order[j..].iter() {
+            let take = (self.counts[i] as f64 * self.sizes[i]).min(room);
+            bound += take / self.sizes[i] * self.values[i];
+            room -= take;
+        }
+        if j == self.order.len() || bound <= self.best.0 + EPS {
+            return;
+        }
+        let i = self.order[j];
+        let most = (((space + EPS) / self.sizes[i]) as usize).min(self.counts[i]);
+        for c in (0..=most).rev() {
+            self.chosen[i] = c;
+            self.branch(j + 1, space - c as f64 * self.sizes[i], value + c as f64 * self.values[i]);
+        }
+        self.chosen[i] = 0;
+    }
+}
+
+/// Gilmore–Gomory LP bound over `counts[i]` items of each distinct size
+/// `sizes[i]`, by column generation with a revised simplex. Every round
+/// gives the valid bound z / max(1, best pattern value) (Farley), so
+/// stopping early only weakens it.
+fn lp_bound(sizes: &[f64], counts: &[usize]) -> usize {
+    let m = sizes.len();
+    // start from the patterns of a single size
+    let mut inverse = vec![vec![0.0; m]; m];
+    let mut x = vec![0.0; m];
+    for i in 0..m {
+        let copies = (CAPACITY / sizes[i]).floor().min(counts[i] as f64);
+        inverse[i][i] = 1.0 / copies;
+        x[i] = counts[i] as f64 / copies;
+    }
+
+    let mut bound = 0.0f64;
+    for _ in 0..LP_ROUNDS {
+        let z = x.iter().sum::<f64>();
+        let duals = (0..m).map(|i| inverse.iter().map(|row| row[i]).sum::<f64>()).collect::<Vec<f64>>();
+        let (value, pattern) = Knapsack::solve(sizes, counts, &duals);
+        bound = bound.max(z / value.max(1.0));
+        if value <= 1.0 + EPS {
+            break;
+        }
+
+        let column = inverse
+            .iter()
+            .map(|row| row.iter().zip(pattern.iter()).map(|(b, &a)| b * a as f64).sum::<f64>())
+            .collect::<Vec<f64>>();
+        let leaving = (0..m)
+            .filter(|&r| column[r] > EPS)
+            .min_by(|&a, &b| (x[a] / column[a]).total_cmp(&(x[b] / column[b])));
+        let r = match leaving {
+            Some(r) => r,
+            None => break,
+        };
+        let pivot = column[r];
+        inverse[r].iter_mut().for_each(|b| *b /= pivot);
+        x[r] /= pivot;
+        let (row, rate) = (inverse[r].clone(), x[r]);
+        for q in (0..m).filter(|&q| q != r && column[q] != 0.0) {
+            for (b, p) in inverse[q].iter_mut().zip(row.iter()) {
+                *b -= column[q] * p;
+            }
+            x[q] -= column[q] * rate;
+        }
+    }
+    // the LP optimum itself carries some rounding error
+    ceil(bound - 1e-6)
+}
+
+/// Bin completion (Korf): bins are filled one at a time, each with its
+/// largest remaining item and a maximal set of others.
+struct Search {
+    sizes  : Vec<f64>,   // distinct, decreasing
+    counts : Vec<usize>, // remaining items of each size
+    best   : usize,
+    lower  : usize,
+    nodes  : usize,
+    seen   : HashMap<(Vec<usize>, Option<Vec<usize>>), usize>, // fewest bins that reached a node
+}
+
+impl Search {
+    fn remaining(&self) -> Vec<f64> {
+        let mut items = Vec::new();
+        for (&s, &c) in self.sizes.iter().zip(self.counts.iter()) {
+            items.extend(std::iter::repeat_n(s, c));
+        }
+        items
+    }
+
+    /// Pushes every maximal set of remaining items, sizes from `j` on, that
+    /// fits into `space`, with its total size.
+    fn completions(&self, j: usize, space: f64, chosen: &mut Vec<usize>, out: &mut Vec<(f64, Vec<usize>)>) {
+        if j == self.sizes.len() {
+            let fits = (0..self.sizes.len()).any(|i| self.counts[i] > chosen[i] && self.sizes[i] <= space + EPS);
+            if !fits {
+                let sum = chosen.iter().zip(self.sizes.iter()).map(|(&c, &s)| c as f64 * s).sum();
+                out.push((sum, chosen.clone()));
+            }
+            return;
+        }
+        let s = self.sizes[j];
+        let most = (((space + EPS) / s) as usize).min(self.counts[j]);
+        for c in (0..=most).rev() {
+            chosen[j] = c;
+            self.completions(j + 1, space - c as f64 * s, chosen, out);
+        }
+        chosen[j] = 0;
+    }
+
+    /// Whether completion `a` dominates `b`, i.e. b's items fit into bins
+    /// the size of a's items. Checked with First Fit Decreasing, which may
+    /// miss some.
+    fn dominates(&self, a: &[usize], b: &[usize]) -> bool {
+        let mut room = Vec::new();
+        for (&s, &c) in self.sizes.iter().zip(a.iter()) {
+            room.extend(std::iter::repeat_n(s, c));
+        }
+        for (&s, &c) in self.sizes.iter().zip(b.iter()) {
+            for _ in 0..c {
+                match room.iter_mut().find(|r| **r + EPS >= s) {
+                    Some(r) => *r -= s,
+                    None => return false,
+                }
+            }
+        }
+        true
+    }
+
+    /// `last` is the largest item and completion of the bin before.
+    fn branch(&mut self, bins: usize, last: Option<(usize, &[usize])>) {
+        self.nodes += 1;
+        if self.best == self.lower || self.nodes > NODE_LIMIT {
+            return;
+        }
+        let first = match self.counts.iter().position(|&c| c > 0) {
+            Some(first) => first,
+            None => {
+                self.best = self.best.min(bins);
+                return;
+            },
+        };
+        if bins + l2_bound(&self.remaining()) >= self.best {
+            return;
+        }
+        // the subtree depends on the items left and on the completion the
+        // next bin must not exceed, if it has the same largest item
+        let bound = last.filter(|&(item, _)| item == first).map(|(_, prev)| prev.to_vec());
+        let key = (self.counts.clone(), bound);
+        match self.seen.get(&key) {
+            Some(&seen) if seen <= bins => return,
+            _ => {
+                self.seen.insert(key, bins);
+            },
+        }
+
+        self.counts[first] -= 1;
+        let mut options = Vec::new();
+        let mut chosen = vec![0; self.sizes.len()];
+        self.completions(0, 1.0 - self.sizes[first], &mut chosen, &mut options);
+        options.sort_by(|a, b| b.0.total_cmp(&a.0));
+        let mut kept: Vec<Vec<usize>> = Vec::new();
+        for (_, chosen) in options {
+            if !kept.iter().any(|a| self.dominates(a, &chosen)) {
+                kept.push(chosen);
+            }
+        }
+        for chosen in kept.iter() {
+            // bins with equal largest items take their completions in order
+            if matches!(last, Some((item, prev)) if item == first && chosen.as_slice() > prev) {
+                continue;
+            }
+            for (count, c) in self.counts.iter_mut().zip(chosen.iter()) {
+                *count -= c;
+            }
+            self.branch(bins + 1, Some((first, chosen)));
+            for (count, c) in self.counts.iter_mut().zip(chosen.iter()) {
+                *count += c;
+            }
+        }
+        self.counts[first] += 1;
+    }
+}
+
+/// Optimal number of bins for a sequence, as far as the search got.
+pub struct Optimum {
+    pub bins   : usize,
+    pub proven : bool, // false if the node limit cut the search short
+}
+
+/// Number of bins in an optimal packing, by bin completion starting from
+/// First Fit Decreasing, the L2 bound and the LP bound. Past `NODE_LIMIT`
+/// nodes the best packing found so far is returned, unproven.
+pub fn exact_packing(arr: &[f64]) -> Optimum {
+    let mut items = arr.to_vec();
+    items.sort_by(|a, b| b.total_cmp(a));
+
+    let lower = l2_bound(&items);
+    let upper = first_fit_decreasing(&items);
+    if lower == upper {
+        return Optimum { bins: upper, proven: true };
+    }
+
+    let mut sizes: Vec<f64> = Vec::new();
+    let mut counts: Vec<usize> = Vec::new();
+    for &s in items.iter() {
+        match counts.last_mut() {
+            Some(count) if sizes.last() == Some(&s) => *count += 1,
+            _ => {
+                sizes.push(s);
+                counts.push(1);
+            },
+        }
+    }
+    let lower = lower.max(lp_bound(&sizes, &counts));
+    if lower >= upper {
+        return Optimum { bins: upper, proven: true };
+    }
+
+    let mut search = Search {
+        sizes,
+        counts,
+        best  : upper,
+        lower,
+        nodes : 0,
+        seen  : HashMap::new(),
+    };
+    search.branch(0, None);
+    Optimum {
+        bins   : search.best,
+        proven : search.nodes <= NODE_LIMIT,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fastrand::Rng;
+
+    /// Fewest bins by dynamic programming over subsets: for each set of
+    /// packed items, the fewest bins and then the least load of the last.
+    fn brute_force(items: &[f64]) -> usize {
+        let n = items.len();
+        let mut best = vec![(usize::MAX, 0.0); 1 << n];
+        best[0] = (1, 0.0);
+        for mask in 0..1usize << n {
+            let (bins, load) = best[mask];
+            for (i, &s) in items.iter().enumerate().filter(|&(i, _)| mask & 1 << i == 0) {
+                let next = if load + s <= CAPACITY { (bins, load + s) } else { (bins + 1, s) };
+                let entry = &mut best[mask | 1 << i];
+                if next.0 < entry.0 || (next.0 == entry.0 && next.1 < entry.1) {
+                    *entry = next;
+                }
+            }
+        }
+        best[(1 << n) - 1].0
+    }
+
+    fn check(items: &[f64]) {
+        let optimum = brute_force(items);
+        let mut sorted = items.to_vec();
+        sorted.sort_by(|a, b| b.total_cmp(a));
+        assert!(l2_bound(&sorted) <= optimum, "L2 above the optimum for {:?}", items);
+        let exact = exact_packing(items);
+        assert!(exact.proven, "not proven for {:?}", items);
+        assert_eq!(exact.bins, optimum, "wrong optimum for {:?}", items);
+    }
+
+    /// Sizes in hundredths, as decimals that f64 rounds.
+    fn decimals(cents: &[usize]) -> Vec<f64> {
+        cents.iter().map(|&c| c as f64 / 100.0).collect()
+    }
+
+    #[test]
+    fn complementary_decimals_fit() {
+        let items = decimals(&[67, 34, 34, 34, 34, 33, 33, 33, 33, 33, 23]);
+        check(&items);
+        assert_eq!(exact_packing(&items).bins, 4);
+    }
+
+    #[test]
+    fn random_decimals_match_brute_force() {
+        let rand = Rng::with_seed(45);
+        for _ in 0..2000 {
+            let cents = (0..rand.usize(4..=12)).map(|_| rand.usize(1..100)).collect::<Vec<usize>>();
+            check(&decimals(&cents));
+        }
+    }
+
+    #[test]
+    fn near_complementary_decimals_match_brute_force() {
+        let rand = Rng::with_seed(46);
+        for _ in 0..2000 {
+            let mut cents = Vec::new();
+            while cents.len() < 12 {
+                let c = rand.usize(20..80);
+                cents.push(c);
+                cents.push((100 - c + rand.usize(0..3)).saturating_sub(1));
+            }
+            cents.truncate(rand.usize(4..=12));
+            check(&decimals(&cents));
+        }
+    }
+
+    /// Few distinct sizes give many bins with the same largest item, where
+    /// the symmetry rule and the memo interact.
+    #[test]
+    fn repeated_sizes_match_brute_force() {
+        let rand = Rng::with_seed(47);
+        for _ in 0..2000 {
+            let sizes = (0..rand.usize(2..=4)).map(|_| rand.usize(10..70)).collect::<Vec<usize>>();
+            let cents = (0..rand.usize(6..=12)).map(|_| sizes[rand.usize(0..sizes.len())]).collect::<Vec<usize>>();
+            check(&decimals(&cents));
+        }
+    }
+}
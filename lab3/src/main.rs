@@ -1,8 +1,19 @@
 mod bin_pack;
+mod bins;
+mod bounded;
 mod distribution;
+mod exact;
+mod plots;
+mod size;
+mod vector;
 
 use bin_pack::*;
+use bounded::*;
 use distribution::*;
+use exact::*;
+use plots::*;
+use size::*;
+use vector::*;
 
 use std::fs::File;
 use std::io::prelude::*;
@@ -10,38 +21,90 @@ use fastrand::Rng;
 
 const NUM_OF_TESTS: usize = 100_000;
 const SEQ_LEN: usize = 100;
+const LONG_SEQ_LEN: usize = 1_000_000;
 const N: usize = 10;
+const HARMONIC_K: usize = 10;
+const NEXT_K: usize = 2;
+const NUM_OF_VECTOR_TESTS: usize = 10_000;
+const DIMS: [usize; 2] = [2, 3];
+const VECTOR_MAX: f64 = 0.5;
 
-fn experiment() {
-    let mut bin_pack_types = [
+/// What the number of bins is divided by.
+#[derive(Clone, Copy)]
+enum Opt {
+    Exact,      // exact_packing
+    LowerBound, // optimal_packing, ceil of the total size
+}
+
+fn bin_pack_types<S: Size>() -> Vec<BinPacking<S>> {
+    vec![
         BinPacking::Next,
         BinPacking::First,
         BinPacking::Best,
         BinPacking::Worst,
-        BinPacking::Random(Rng::new())
-    ];
-    
-    let mut distributions = [
+        BinPacking::Random(Rng::new()),
+        BinPacking::Harmonic(Harmonic::new(HARMONIC_K)),
+        BinPacking::RefinedHarmonic(Harmonic::refined()),
+        BinPacking::ModifiedHarmonic(Harmonic::modified()),
+        BinPacking::NextK(NextK::new(NEXT_K)),
+    ]
+}
+
+fn distributions() -> [Distribution; 4] {
+    [
         Distribution::Uni(UniDistribution::new(N)),
         Distribution::Har(ArrDistribution::harmonic(N)),
         Distribution::Bih(ArrDistribution::biharmonic(N)),
         Distribution::Geo(GeoDistribution::new(0.5, N))
-    ];
+    ]
+}
+
+fn experiment<S: Size>(opt: Opt) {
+    let mut bin_pack_types = bin_pack_types::<S>();
+    let mut distributions = distributions();
 
     for distribution in distributions.iter_mut() { 
         let mut sequences = Vec::with_capacity(NUM_OF_TESTS);
         for _ in 0..NUM_OF_TESTS {
-            sequences.push(distribution.gen_seq(SEQ_LEN));
+            sequences.push(distribution.gen_seq::<S>(SEQ_LEN));
+        }
+
+        let bounds = sequences.iter().map(|seq| optimal_packing(seq)).collect::<Vec<f64>>();
+        let optima = match opt {
+            Opt::Exact => sequences
+                .iter()
+                .map(|seq| exact_packing(&seq.iter().map(|r| r.fraction()).collect::<Vec<f64>>()))
+                .collect::<Vec<Optimum>>(),
+            Opt::LowerBound => Vec::new(),
+        };
+        let opts = match opt {
+            Opt::Exact => optima.iter().map(|optimum| optimum.bins as f64).collect::<Vec<f64>>(),
+            Opt::LowerBound => bounds.clone(),
+        };
+        if let Opt::Exact = opt {
+            // how far ceil(sum) is from the real optimum
+            let filename = format!("data/dist_{}opt_gap.txt", distribution.name());
+            let mut file = File::create(filename).unwrap();
+            let gaps = opts.iter().zip(bounds.iter()).map(|(opt, bound)| opt - bound).collect::<Vec<f64>>();
+            let mean_bound = bounds.iter().sum::<f64>() / NUM_OF_TESTS as f64;
+            let mean_opt = opts.iter().sum::<f64>() / NUM_OF_TESTS as f64;
+            let mean_gap = gaps.iter().sum::<f64>() / NUM_OF_TESTS as f64;
+            let with_gap = gaps.iter().filter(|&&gap| gap > 0.0).count() as f64 / NUM_OF_TESTS as f64;
+
+            let proven = optima.iter().filter(|optimum| optimum.proven).count() as f64 / NUM_OF_TESTS as f64;
+
+            let line = format!("{};{};{};{};{}", mean_bound, mean_opt, mean_gap, with_gap, proven);
+            file.write_all(line.as_bytes()).unwrap();
         }
         for bin_pack_type in bin_pack_types.iter_mut() {
             let filename = format!("data/dist_{}bp_{}.txt", distribution.name(), bin_pack_type.name());
             let mut file = File::create(filename).unwrap();
 
             let mut avg = 0.0;
-            for seq in sequences.iter() {
+            for (seq, opt) in sequences.iter().zip(opts.iter()) {
                 let mut bin_pack = BinPack::new(bin_pack_type);
                 bin_pack.pack(seq);
-                avg += (bin_pack.num_of_bins() as f64) / optimal_packing(seq);
+                avg += (bin_pack.num_of_bins() as f64) / opt;
             }
             avg /= NUM_OF_TESTS as f64;
 
@@ -52,8 +115,122 @@ fn experiment() {
     
 }
 
+/// One long sequence per distribution, against the lower bound. Random
+/// Fit scans every bin, so it is left out.
+fn long_experiment<S: Size>() {
+    let mut bin_pack_types = bin_pack_types::<S>();
+    bin_pack_types.retain(|bin_pack_type| !matches!(bin_pack_type, BinPacking::Random(_)));
+    let mut distributions = distributions();
+
+    for distribution in distributions.iter_mut() {
+        let seq = distribution.gen_seq::<S>(LONG_SEQ_LEN);
+        let bound = optimal_packing(&seq);
+        for bin_pack_type in bin_pack_types.iter_mut() {
+            let filename = format!("data/dist_{}long_{}.txt", distribution.name(), bin_pack_type.name());
+            let mut file = File::create(filename).unwrap();
+
+            let mut bin_pack = BinPack::new(bin_pack_type);
+            bin_pack.pack(&seq);
+            let ratio = bin_pack.num_of_bins() as f64 / bound;
+
+            let line = format!("{}", ratio);
+            file.write_all(line.as_bytes()).unwrap();
+        }
+    }
+}
+
+/// Packs one sequence per distribution with every algorithm, writing each
+/// item's bin and a chart of the bins.
+fn draw_experiment<S: Size>() {
+    let mut bin_pack_types = bin_pack_types::<S>();
+    let mut distributions = distributions();
+
+    for distribution in distributions.iter_mut() {
+        let seq = distribution.gen_seq::<S>(SEQ_LEN);
+        for bin_pack_type in bin_pack_types.iter_mut() {
+            let name = format!("data/dist_{}packing_{}", distribution.name(), bin_pack_type.name());
+            let mut file = File::create(format!("{}.txt", name)).unwrap();
+
+            let mut bin_pack = BinPack::recording(bin_pack_type);
+            bin_pack.pack(&seq);
+
+            for (item, (size, bin)) in bin_pack.items().iter().zip(bin_pack.assignment()).enumerate() {
+                let line = format!("{};{};{}\n", item, size.fraction(), bin);
+                file.write_all(line.as_bytes()).unwrap();
+            }
+            let fill = bin_pack.levels().iter().map(|level| level.fraction()).sum::<f64>() / bin_pack.num_of_bins() as f64;
+            let caption = format!("{} on {} distribution, {} bins, mean fill {:.3}", bin_pack.name(), distribution.name(), bin_pack.num_of_bins(), fill);
+            plot_packing(&format!("{}.png", name), &bin_pack, &caption).unwrap();
+        }
+    }
+}
+
+fn vector_experiment<S: Size>() {
+    let bin_pack_types = [
+        VectorBinPacking::Next,
+        VectorBinPacking::First,
+        VectorBinPacking::Best(Fitness::L2),
+        VectorBinPacking::Best(Fitness::DotProduct),
+        VectorBinPacking::Best(Fitness::MaxDimension),
+    ];
+
+    for d in DIMS {
+        let mut distributions = [
+            VectorDistribution::new(VectorKind::Independent, d, VECTOR_MAX),
+            VectorDistribution::new(VectorKind::Correlated, d, VECTOR_MAX),
+            VectorDistribution::new(VectorKind::AntiCorrelated, d, VECTOR_MAX),
+        ];
+
+        for distribution in distributions.iter_mut() {
+            let mut sequences = Vec::with_capacity(NUM_OF_VECTOR_TESTS);
+            for _ in 0..NUM_OF_VECTOR_TESTS {
+                sequences.push(distribution.gen_seq::<S>(SEQ_LEN));
+            }
+            let bounds = sequences.iter().map(|seq| vector_lower_bound(seq) as f64).collect::<Vec<f64>>();
+
+            for bin_pack_type in bin_pack_types.iter() {
+                let filename = format!("data/vec{}_dist_{}bp_{}.txt", d, distribution.name(), bin_pack_type.name());
+                let mut file = File::create(filename).unwrap();
+
+                // ratio to the bound, and how full bins are in their fullest dimension
+                let mut avg = 0.0;
+                let mut fill = 0.0;
+                for (seq, bound) in sequences.iter().zip(bounds.iter()) {
+                    let mut bin_pack = VectorBinPack::new(bin_pack_type, d);
+                    bin_pack.pack(seq);
+                    avg += (bin_pack.num_of_bins() as f64) / bound;
+                    fill += bin_pack
+                        .levels()
+                        .iter()
+                        .map(|load| load.iter().map(|level| level.fraction()).fold(0.0, f64::max))
+                        .sum::<f64>() / bin_pack.num_of_bins() as f64;
+                }
+                avg /= NUM_OF_VECTOR_TESTS as f64;
+                fill /= NUM_OF_VECTOR_TESTS as f64;
+
+                let line = format!("{};{}", avg, fill);
+                file.write_all(line.as_bytes()).unwrap();
+            }
+        }
+    }
+}
+
 fn main() {
     let now = std::time::Instant::now();
-    experiment();
+    let mode = std::env::args().nth(1);
+    let size = std::env::args().nth(2);
+    match (mode.as_deref(), size.as_deref()) {
+        (Some("bound") | None, Some("float")) => experiment::<f64>(Opt::LowerBound),
+        (Some("bound") | None, _) => experiment::<Fixed>(Opt::LowerBound),
+        (Some("exact"), Some("float")) => experiment::<f64>(Opt::Exact),
+        (Some("exact"), _) => experiment::<Fixed>(Opt::Exact),
+        (Some("long"), Some("float")) => long_experiment::<f64>(),
+        (Some("long"), _) => long_experiment::<Fixed>(),
+        (Some("draw"), Some("float")) => draw_experiment::<f64>(),
+        (Some("draw"), _) => draw_experiment::<Fixed>(),
+        (Some("vector"), Some("float")) => vector_experiment::<f64>(),
+        (Some("vector"), _) => vector_experiment::<Fixed>(),
+        (Some(other), _) => panic!("unknown mode {:?}, expected exact, bound, long, draw or vector", other),
+    }
     println!("Time elapsed: {} s", now.elapsed().as_secs_f64());
 }
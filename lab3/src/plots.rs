@@ -0,0 +1,58 @@
+use crate::bin_pack::*;
+use crate::size::*;
+use plotters::coord::Shift;
+use plotters::prelude::*;
+
+fn draw_packing<DB: DrawingBackend, S: Size>(root: &DrawingArea<DB, Shift>, bin_pack: &BinPack<S>, caption: &str) -> Result<(), Box<dyn std::error::Error>>
+where
+    DB::ErrorType: 'static,
+{
+    root.fill(&WHITE)?;
+
+    let bins = bin_pack.num_of_bins();
+    let mut chart = ChartBuilder::on(root)
+        .x_label_area_size(35)
+        .y_label_area_size(40)
+        .margin(20)
+        .caption(caption, ("sans-serif", 30.0).into_font())
+        .build_cartesian_2d(0.0f32..bins as f32, 0.0f32..1.05f32)?;
+
+    chart
+        .configure_mesh()
+        .disable_x_mesh()
+        .disable_y_mesh()
+        .x_desc("Bin")
+        .y_desc("Fill level")
+        .draw()?;
+
+    // a bar per bin, its items stacked in the order they came
+    let items = bin_pack.items();
+    for (bin, contents) in bin_pack.contents().iter().enumerate() {
+        let mut level = 0.0;
+        let x = bin as f32;
+        chart.draw_series(contents.iter().map(|&item| {
+            let top = level + items[item].fraction() as f32;
+            let color = Palette99::pick(item).filled();
+            let bar = Rectangle::new([(x + 0.1, level), (x + 0.9, top)], color);
+            level = top;
+            bar
+        }))?;
+    }
+    chart.draw_series(LineSeries::new([(0.0, 1.0), (bins as f32, 1.0)], BLACK.stroke_width(1)))?;
+
+    root.present()?;
+    Ok(())
+}
+
+/// Draws a packing as a bar per bin, its items stacked. The file is an
+/// SVG if its name ends in .svg and a PNG otherwise.
+pub fn plot_packing<S: Size>(out_file_name: &str, bin_pack: &BinPack<S>, caption: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let size = (1024, 768);
+    if out_file_name.ends_with(".svg") {
+        draw_packing(&SVGBackend::new(out_file_name, size).into_drawing_area(), bin_pack, caption)?;
+    } else {
+        draw_packing(&BitMapBackend::new(out_file_name, size).into_drawing_area(), bin_pack, caption)?;
+    }
+    println!("Result has been saved to {}", out_file_name);
+    Ok(())
+}
@@ -0,0 +1,140 @@
+use std::iter::Sum;
+use std::ops::{Add, AddAssign, Sub};
+
+/// Bits of the fractional part of a `Fixed` size.
+pub const FIXED_BITS: u32 = 32;
+
+/// Item sizes in units of 1/2^32 of a bin, so that loads and fit tests are
+/// exact.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub struct Fixed(u64);
+
+impl Add for Fixed {
+    type Output = Fixed;
+
+    fn add(self, other: Fixed) -> Fixed {
+        Fixed(self.0 + other.0)
+    }
+}
+
+impl AddAssign for Fixed {
+    fn add_assign(&mut self, other: Fixed) {
+        self.0 += other.0;
+    }
+}
+
+impl Sub for Fixed {
+    type Output = Fixed;
+
+    fn sub(self, other: Fixed) -> Fixed {
+        Fixed(self.0 - other.0)
+    }
+}
+
+impl Sum for Fixed {
+    fn sum<I: Iterator<Item = Fixed>>(iter: I) -> Fixed {
+        Fixed(iter.map(|size| size.0).sum())
+    }
+}
+
+/// Type of item sizes and bin loads.
+pub trait Size: Copy + PartialOrd + Add<Output = Self> + AddAssign + Sub<Output = Self> + Sum {
+    const ZERO: Self;
+    /// Capacity of a bin.
+    const CAPACITY: Self;
+    /// Above every load, no item fits with it.
+    const FULL: Self;
+
+    /// Size closest to a fraction of a bin in [0, 1].
+    fn from_fraction(x: f64) -> Self;
+
+    /// Fraction of a bin.
+    fn fraction(self) -> f64;
+
+    /// Whether an item of this size fits in a bin at `load`.
+    fn fits(self, load: Self) -> bool;
+
+    /// At least the largest load the item fits with.
+    fn room(self) -> Self;
+
+    /// Copies of the item an empty bin takes.
+    fn per_bin(self) -> usize;
+
+    /// Bins a total of this size needs at least, ceil(total / capacity).
+    fn bins_for(self) -> usize;
+
+    /// Key that orders loads like they do, for the multiset of loads.
+    fn key(self) -> u64;
+}
+
+impl Size for f64 {
+    const ZERO: Self = 0.0;
+    const CAPACITY: Self = 1.0;
+    const FULL: Self = f64::INFINITY;
+
+    fn from_fraction(x: f64) -> Self {
+        x
+    }
+
+    fn fraction(self) -> f64 {
+        self
+    }
+
+    fn fits(self, load: Self) -> bool {
+        load + self <= 1.0
+    }
+
+    // rounding in `fits` may accept loads a little above 1 - r
+    fn room(self) -> Self {
+        1.0 - self + 1e-9
+    }
+
+    fn per_bin(self) -> usize {
+        (1.0 / self).floor() as usize
+    }
+
+    fn bins_for(self) -> usize {
+        self.ceil() as usize
+    }
+
+    // bits of non-negative floats sort like them
+    fn key(self) -> u64 {
+        self.to_bits()
+    }
+}
+
+impl Size for Fixed {
+    const ZERO: Self = Fixed(0);
+    const CAPACITY: Self = Fixed(1 << FIXED_BITS);
+    const FULL: Self = Fixed(u64::MAX);
+
+    /// Sizes below one unit round up to it.
+    fn from_fraction(x: f64) -> Self {
+        let capacity = Self::CAPACITY.0;
+        Fixed(((x * capacity as f64).round() as u64).clamp(1, capacity))
+    }
+
+    fn fraction(self) -> f64 {
+        self.0 as f64 / Self::CAPACITY.0 as f64
+    }
+
+    fn fits(self, load: Self) -> bool {
+        load <= Self::CAPACITY - self
+    }
+
+    fn room(self) -> Self {
+        Self::CAPACITY - self
+    }
+
+    fn per_bin(self) -> usize {
+        (Self::CAPACITY.0 / self.0) as usize
+    }
+
+    fn bins_for(self) -> usize {
+        self.0.div_ceil(Self::CAPACITY.0) as usize
+    }
+
+    fn key(self) -> u64 {
+        self.0
+    }
+}
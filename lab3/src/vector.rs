@@ -0,0 +1,272 @@
+use crate::exact::*;
+use crate::size::*;
+use fastrand::Rng;
+
+/// How Best Fit ranks the bins a d-dimensional item fits in, loads as
+/// fractions of the capacity in each dimension.
+#[derive(Clone, Copy)]
+pub enum Fitness {
+    /// Least Euclidean norm of the residual capacity after placing it.
+    L2,
+    /// Greatest dot product of the item and the residual capacity before
+    /// placing it (the alignment score of Tetris).
+    DotProduct,
+    /// Fullest by the largest load over the dimensions after placing it.
+    MaxDimension,
+}
+
+impl Fitness {
+    /// Higher is better.
+    fn score<S: Size>(&self, r: &[S], load: &[S]) -> f64 {
+        let dims = r.iter().zip(load.iter()).map(|(r, load)| (r.fraction(), load.fraction()));
+        match self {
+            Fitness::L2 => -dims.map(|(r, load)| (1.0 - load - r).powi(2)).sum::<f64>(),
+            Fitness::DotProduct => dims.map(|(r, load)| r * (1.0 - load)).sum(),
+            Fitness::MaxDimension => dims.map(|(r, load)| load + r).fold(0.0, f64::max),
+        }
+    }
+}
+
+pub enum VectorBinPacking {
+    Next,
+    First,
+    Best(Fitness),
+}
+
+impl VectorBinPacking {
+    pub fn name(&self) -> &str {
+        match self {
+            VectorBinPacking::Next => "NEXT",
+            VectorBinPacking::First => "FIRST",
+            VectorBinPacking::Best(Fitness::L2) => "BEST_L2",
+            VectorBinPacking::Best(Fitness::DotProduct) => "BEST_DOT",
+            VectorBinPacking::Best(Fitness::MaxDimension) => "BEST_MAX",
+        }
+    }
+
+    pub fn find_idx<S: Size>(&self, r: &[S], bins: &[Vec<S>]) -> Option<usize> {
+        let fits = |load: &Vec<S>| r.iter().zip(load.iter()).all(|(r, &load)| r.fits(load));
+        match self {
+            VectorBinPacking::Next => {
+                let last = bins.len() - 1;
+                if fits(&bins[last]) {
+                    Some(last)
+                } else {
+                    None
+                }
+            },
+            VectorBinPacking::First => bins.iter().position(fits),
+            VectorBinPacking::Best(fitness) => {
+                bins.iter()
+                .enumerate()
+                .filter(|(_, load)| fits(load))
+                .max_by(|(_, a), (_, b)| fitness.score(r, a).total_cmp(&fitness.score(r, b)))
+                .map(|(i, _)| i)
+            },
+        }
+    }
+}
+
+/// `BinPack` for items with a size in each of d dimensions, each a
+/// fraction of the bin's capacity in that dimension. It stays apart from
+/// `BinPack`: `Bins` answers First/Best/Worst Fit from loads ordered on a
+/// line, which vectors of loads are not, and the bounded-space algorithms
+/// class items by a single size, so the heuristics here scan the bins.
+pub struct VectorBinPack<'a, S: Size = Fixed> {
+    bins : Vec<Vec<S>>,
+    bin_pack : &'a VectorBinPacking
+}
+
+impl<'a, S: Size> VectorBinPack<'a, S> {
+    pub fn new(bin_pack : &'a VectorBinPacking, d: usize) -> Self {
+        VectorBinPack {
+            bins: vec![ vec![S::ZERO; d] ],
+            bin_pack
+        }
+    }
+
+    pub fn add_val(&mut self, r: &[S]) {
+        assert_eq!(r.len(), self.bins[0].len(), "item of the wrong dimension");
+        let idx = match self.bin_pack.find_idx(r, &self.bins) {
+            Some(idx) => idx,
+            None => {
+                self.bins.push(vec![S::ZERO; r.len()]);
+                self.bins.len() - 1
+            },
+        };
+        for (load, &r) in self.bins[idx].iter_mut().zip(r.iter()) {
+            *load += r;
+        }
+    }
+
+    pub fn pack(&mut self, rs: &[Vec<S>]) {
+        for r in rs {
+            self.add_val(r);
+        }
+    }
+
+    pub fn num_of_bins(&self) -> usize {
+        self.bins.len()
+    }
+
+    /// Load of each bin in each dimension.
+    pub fn levels(&self) -> &[Vec<S>] {
+        &self.bins
+    }
+}
+
+/// Lower bound on the bins of a d-dimensional packing: packing the items'
+/// sizes in a single dimension is a relaxation, so the largest L2 bound
+/// over the dimensions holds.
+pub fn vector_lower_bound<S: Size>(items: &[Vec<S>]) -> usize {
+    let d = items.first().map_or(0, |item| item.len());
+    (0..d)
+        .map(|dim| {
+            let mut sizes = items.iter().map(|item| item[dim].fraction()).collect::<Vec<f64>>();
+            sizes.sort_by(|a, b| b.total_cmp(a));
+            l2_bound(&sizes)
+        })
+        .max()
+        .unwrap_or(0)
+}
+
+/// How the dimensions of an item relate, e.g. the CPU and memory demands
+/// of a VM.
+pub enum VectorKind {
+    /// Every dimension uniform on [0, max).
+    Independent,
+    /// A common uniform demand, each dimension within `max / 10` of it.
+    Correlated,
+    /// Demands summing to `max`, spread at random over the
+    /// dimensions, so CPU-heavy items are light on memory and vice versa.
+    AntiCorrelated,
+}
+
+pub struct VectorDistribution {
+    kind : VectorKind,
+    d : usize,
+    max : f64,
+    pub generator: Rng
+}
+
+impl VectorDistribution {
+    pub fn new(kind: VectorKind, d: usize, max: f64) -> Self {
+        VectorDistribution {
+            kind,
+            d,
+            max,
+            generator: Rng::new()
+        }
+    }
+
+    pub fn name(&self) -> String {
+        match self.kind {
+            VectorKind::Independent => "independent".to_owned(),
+            VectorKind::Correlated => "correlated".to_owned(),
+            VectorKind::AntiCorrelated => "anticorrelated".to_owned(),
+        }
+    }
+
+    fn generate(&mut self) -> Vec<f64> {
+        let rand = &self.generator;
+        match self.kind {
+            VectorKind::Independent => (0..self.d).map(|_| rand.f64() * self.max).collect(),
+            VectorKind::Correlated => {
+                let base = rand.f64() * self.max;
+                (0..self.d)
+                    .map(|_| (base + (rand.f64() - 0.5) * self.max / 5.0).clamp(0.0, self.max))
+                    .collect()
+            },
+            VectorKind::AntiCorrelated => {
+                let weights = (0..self.d).map(|_| rand.f64()).collect::<Vec<f64>>();
+                let total = weights.iter().sum::<f64>();
+                weights.iter().map(|w| (w / total * self.max).min(1.0)).collect()
+            },
+        }
+    }
+
+    pub fn gen_seq<S: Size>(&mut self, n: usize) -> Vec<Vec<S>> {
+        (0..n)
+            .map(|_| self.generate().into_iter().map(S::from_fraction).collect())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bins() -> Vec<Vec<f64>> {
+        vec![vec![0.9, 0.1], vec![0.2, 0.6], vec![0.7, 0.0], vec![0.3, 0.3]]
+    }
+
+    #[test]
+    fn heuristics_pick_their_bin() {
+        let r = [0.25, 0.25];
+        let cases = [
+            (VectorBinPacking::Next, Some(3)),
+            (VectorBinPacking::First, Some(1)),
+            (VectorBinPacking::Best(Fitness::L2), Some(1)),
+            (VectorBinPacking::Best(Fitness::DotProduct), Some(3)),
+            (VectorBinPacking::Best(Fitness::MaxDimension), Some(2)),
+        ];
+        for (bin_pack, idx) in cases {
+            assert_eq!(bin_pack.find_idx(&r, &bins()), idx, "{}", bin_pack.name());
+        }
+    }
+
+    #[test]
+    fn heuristics_open_a_bin_only_when_they_must() {
+        // fits the third bin alone
+        let r = [0.2, 0.75];
+        assert_eq!(VectorBinPacking::Next.find_idx(&r, &bins()), None);
+        assert_eq!(VectorBinPacking::First.find_idx(&r, &bins()), Some(2));
+        for fitness in [Fitness::L2, Fitness::DotProduct, Fitness::MaxDimension] {
+            assert_eq!(VectorBinPacking::Best(fitness).find_idx(&r, &bins()), Some(2));
+        }
+        assert_eq!(VectorBinPacking::First.find_idx(&[0.9, 0.9], &bins()), None);
+    }
+
+    /// Fewest bins the items fit in, trying every bin for every item.
+    fn brute_force(items: &[Vec<Fixed>], bins: &mut Vec<Vec<Fixed>>, best: &mut usize) {
+        if bins.len() >= *best {
+            return;
+        }
+        let Some((r, rest)) = items.split_first() else {
+            *best = bins.len();
+            return;
+        };
+        for i in 0..bins.len() {
+            if r.iter().zip(bins[i].iter()).all(|(r, &load)| r.fits(load)) {
+                for (load, &r) in bins[i].iter_mut().zip(r.iter()) {
+                    *load += r;
+                }
+                brute_force(rest, bins, best);
+                for (load, &r) in bins[i].iter_mut().zip(r.iter()) {
+                    *load = *load - r;
+                }
+            }
+        }
+        bins.push(r.clone());
+        brute_force(rest, bins, best);
+        bins.pop();
+    }
+
+    #[test]
+    fn lower_bound_below_optimum() {
+        for (seed, kind) in [VectorKind::Independent, VectorKind::Correlated, VectorKind::AntiCorrelated].into_iter().enumerate() {
+            let mut distribution = VectorDistribution::new(kind, 2 + seed % 2, 0.7);
+            distribution.generator = Rng::with_seed(seed as u64);
+            for _ in 0..300 {
+                let n = distribution.generator.usize(1..=8);
+                let items = distribution.gen_seq::<Fixed>(n);
+                let mut opt = items.len();
+                brute_force(&items, &mut Vec::new(), &mut opt);
+                assert!(vector_lower_bound(&items) <= opt, "{:?}", items);
+                let mut bin_pack = VectorBinPack::new(&VectorBinPacking::First, items[0].len());
+                bin_pack.pack(&items);
+                assert!(bin_pack.num_of_bins() >= opt);
+            }
+        }
+    }
+}